@@ -11,9 +11,9 @@ async fn main() -> Result<()> {
     let commit_id = "ff0e6ddd616ffabfc02d6943b2aed496fca2c63c";
 
     let host = env::var("HOST")
-        .with_context(|| format!("get environment variable HOST failed"))?;
+        .with_context(|| "get environment variable HOST failed")?;
     let access_token = env::var("ACCESS_TOKEN")
-        .with_context(|| format!("get environment variable ACCESS_TOKEN failed"))?;
+        .with_context(|| "get environment variable ACCESS_TOKEN failed")?;
 
     let cli = Gritlab::builder(host).token(access_token).build()?;
 
@@ -0,0 +1,152 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+use crate::user::BasicUser;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ApprovalGroup {
+    pub id: i64,
+    pub name: String,
+    pub path: String,
+    pub full_path: String,
+    pub avatar_url: Option<String>,
+    pub web_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ApprovedBy {
+    pub user: BasicUser,
+}
+
+/// Approvals of a merge request
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MergeRequestApprovals {
+    pub id: i64,
+    pub iid: i64,
+    pub project_id: i64,
+    pub title: String,
+    pub description: Option<String>,
+    pub state: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub merge_status: String,
+    pub approved: bool,
+    pub approvals_required: Option<i64>,
+    pub approvals_left: Option<i64>,
+    pub require_password_to_approve: Option<bool>,
+    #[serde(default)]
+    pub approved_by: Vec<ApprovedBy>,
+    #[serde(default)]
+    pub suggested_approvers: Vec<BasicUser>,
+    #[serde(default)]
+    pub approvers: Vec<ApprovedBy>,
+    pub user_has_approved: Option<bool>,
+    pub user_can_approve: Option<bool>,
+    pub has_approval_rules: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ApprovalRule {
+    pub id: i64,
+    pub name: String,
+    /// `regular`, `any_approver`, `code_owner` or `report_approver`
+    pub rule_type: String,
+    #[serde(default)]
+    pub eligible_approvers: Vec<BasicUser>,
+    pub approvals_required: i64,
+    #[serde(default)]
+    pub users: Vec<BasicUser>,
+    #[serde(default)]
+    pub groups: Vec<ApprovalGroup>,
+    pub contains_hidden_groups: Option<bool>,
+    pub applies_to_all_protected_branches: Option<bool>,
+    pub report_type: Option<String>,
+    pub section: Option<String>,
+    /// Only returned by the approval state of a merge request
+    #[serde(default)]
+    pub approved_by: Vec<BasicUser>,
+    pub approved: Option<bool>,
+    pub overridden: Option<bool>,
+}
+
+/// Approval state of a merge request, i.e. its rules and who approved them
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ApprovalState {
+    pub approval_rules_overwritten: bool,
+    pub rules: Vec<ApprovalRule>,
+}
+
+/// Approval configuration of a project
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ApprovalConfig {
+    pub approvals_before_merge: Option<i64>,
+    pub reset_approvals_on_push: bool,
+    pub selective_code_owner_removals: Option<bool>,
+    pub disable_overriding_approvers_per_merge_request: bool,
+    pub merge_requests_author_approval: bool,
+    pub merge_requests_disable_committers_approval: bool,
+    pub require_password_to_approve: bool,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct UpdateApprovalConfigOption {
+    pub approvals_before_merge: Option<i64>,
+    pub reset_approvals_on_push: Option<bool>,
+    pub selective_code_owner_removals: Option<bool>,
+    pub disable_overriding_approvers_per_merge_request: Option<bool>,
+    pub merge_requests_author_approval: Option<bool>,
+    pub merge_requests_disable_committers_approval: Option<bool>,
+    pub require_password_to_approve: Option<bool>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ApproveOption {
+    /// Approve only if the head of the merge request is still this sha
+    pub sha: Option<String>,
+    pub approval_password: Option<String>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct CreateApprovalRuleOption {
+    pub name: String,
+    pub approvals_required: i64,
+    pub rule_type: Option<String>,
+    pub user_ids: Option<Vec<i64>>,
+    pub usernames: Option<Vec<String>>,
+    pub group_ids: Option<Vec<i64>>,
+    /// Project-level rules only
+    pub protected_branch_ids: Option<Vec<i64>>,
+    /// Project-level rules only
+    pub applies_to_all_protected_branches: Option<bool>,
+    /// Merge request-level rules only, the project rule to copy
+    pub approval_project_rule_id: Option<i64>,
+}
+
+impl CreateApprovalRuleOption {
+    pub fn new(name: &str, approvals_required: i64) -> Self {
+        Self {
+            name: name.to_string(),
+            approvals_required,
+            ..Default::default()
+        }
+    }
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct UpdateApprovalRuleOption {
+    pub name: Option<String>,
+    pub approvals_required: Option<i64>,
+    pub user_ids: Option<Vec<i64>>,
+    pub usernames: Option<Vec<String>>,
+    pub group_ids: Option<Vec<i64>>,
+    /// Project-level rules only
+    pub protected_branch_ids: Option<Vec<i64>>,
+    /// Project-level rules only
+    pub applies_to_all_protected_branches: Option<bool>,
+    pub remove_hidden_groups: Option<bool>,
+}
@@ -1,16 +1,204 @@
+use std::{fmt, sync::Arc};
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use serde::Deserialize;
+
 use crate::{Error, Result};
 
 #[derive(Debug, Clone)]
 pub enum Auth {
     Token(String),
+    OAuth2(OAuth2Token),
+    /// `CI_JOB_TOKEN` of a running CI job
+    JobToken(String),
+    /// Deploy token, sent with basic auth
+    DeployToken {
+        username: String,
+        token: String,
+    },
     None,
 }
 
 impl Auth {
-    pub fn headers(&self) -> Result<(String, String)> {
-        match &self {
-            &Auth::Token(token) => Ok(("PRIVATE-TOKEN".to_string(), token.to_owned())),
-            &Auth::None => Err(Error::Unauthorized("access token not set".to_string())),
+    /// The auth header to send, `None` for anonymous access
+    pub fn headers(&self) -> Option<(String, String)> {
+        match self {
+            Auth::Token(token) => Some(("PRIVATE-TOKEN".to_string(), token.to_owned())),
+            Auth::OAuth2(token) => Some((
+                "Authorization".to_string(),
+                format!("Bearer {}", token.access_token),
+            )),
+            Auth::JobToken(token) => Some(("JOB-TOKEN".to_string(), token.to_owned())),
+            Auth::DeployToken { username, token } => Some((
+                "Authorization".to_string(),
+                format!(
+                    "Basic {}",
+                    base64::encode(format!("{}:{}", username, token))
+                ),
+            )),
+            Auth::None => None,
+        }
+    }
+
+    pub fn is_anonymous(&self) -> bool {
+        matches!(self, Auth::None)
+    }
+}
+
+/// OAuth2 access token which is refreshed through `/oauth/token` before it expires
+#[derive(Debug, Clone)]
+pub struct OAuth2Token {
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    pub client_id: String,
+    pub client_secret: String,
+    pub redirect_uri: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl OAuth2Token {
+    /// Refresh the token this long before it expires
+    pub const REFRESH_MARGIN_SECS: i64 = 60;
+
+    pub fn new(
+        access_token: impl Into<String>,
+        refresh_token: Option<String>,
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Self {
+        Self {
+            access_token: access_token.into(),
+            refresh_token,
+            client_id: client_id.into(),
+            client_secret: client_secret.into(),
+            redirect_uri: None,
+            expires_at,
         }
     }
+
+    /// Whether the token is about to expire and can be refreshed
+    pub fn needs_refresh(&self) -> bool {
+        let expires_soon = self.expires_at.is_some_and(|expires_at| {
+            expires_at - Duration::seconds(Self::REFRESH_MARGIN_SECS) <= Utc::now()
+        });
+        expires_soon && self.refresh_token.is_some()
+    }
+
+    /// The form posted to `/oauth/token` to refresh the token
+    pub(crate) fn refresh_form(&self) -> Result<Vec<(&'static str, String)>> {
+        let refresh_token = self.refresh_token.clone().ok_or_else(|| {
+            Error::Unauthorized("oauth2 refresh token not set".to_string())
+        })?;
+
+        let mut form = vec![
+            ("grant_type", "refresh_token".to_string()),
+            ("refresh_token", refresh_token),
+            ("client_id", self.client_id.clone()),
+            ("client_secret", self.client_secret.clone()),
+        ];
+        if let Some(redirect_uri) = &self.redirect_uri {
+            form.push(("redirect_uri", redirect_uri.clone()));
+        }
+        Ok(form)
+    }
+
+    /// Apply the `/oauth/token` response
+    pub(crate) fn refreshed(&self, resp: OAuth2TokenResponse) -> Self {
+        let issued_at = resp
+            .created_at
+            .and_then(|secs| Utc.timestamp_opt(secs, 0).single())
+            .unwrap_or_else(Utc::now);
+        Self {
+            access_token: resp.access_token,
+            refresh_token: resp.refresh_token.or_else(|| self.refresh_token.clone()),
+            client_id: self.client_id.clone(),
+            client_secret: self.client_secret.clone(),
+            redirect_uri: self.redirect_uri.clone(),
+            expires_at: resp
+                .expires_in
+                .map(|secs| issued_at + Duration::seconds(secs)),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct OAuth2TokenResponse {
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    pub expires_in: Option<i64>,
+    pub created_at: Option<i64>,
+}
+
+/// Callback invoked with the new token after it has been refreshed, e.g. to persist
+/// the rotated refresh token
+#[derive(Clone)]
+pub struct TokenRefreshCallback(pub Arc<dyn Fn(&OAuth2Token) + Send + Sync>);
+
+impl fmt::Debug for TokenRefreshCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TokenRefreshCallback")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(expires_at: Option<DateTime<Utc>>) -> OAuth2Token {
+        OAuth2Token::new("access", Some("refresh".into()), "id", "secret", expires_at)
+    }
+
+    fn response(
+        refresh_token: Option<&str>,
+        expires_in: Option<i64>,
+    ) -> OAuth2TokenResponse {
+        OAuth2TokenResponse {
+            access_token: "new-access".to_string(),
+            refresh_token: refresh_token.map(String::from),
+            expires_in,
+            created_at: Some(1_600_000_000),
+        }
+    }
+
+    #[test]
+    fn needs_refresh_within_the_margin() {
+        let margin = Duration::seconds(OAuth2Token::REFRESH_MARGIN_SECS);
+        let now = Utc::now();
+        assert!(!token(Some(now + margin + Duration::seconds(30))).needs_refresh());
+        assert!(token(Some(now + margin - Duration::seconds(1))).needs_refresh());
+        assert!(token(Some(now - Duration::seconds(1))).needs_refresh());
+        assert!(!token(None).needs_refresh());
+
+        let mut token = token(Some(now));
+        token.refresh_token = None;
+        assert!(!token.needs_refresh());
+    }
+
+    #[test]
+    fn refreshed_expires_after_created_at() {
+        let token = token(None).refreshed(response(Some("new-refresh"), Some(7200)));
+        assert_eq!(token.access_token, "new-access");
+        assert_eq!(token.refresh_token.as_deref(), Some("new-refresh"));
+        assert_eq!(
+            token.expires_at,
+            Some(Utc.timestamp(1_600_000_000 + 7200, 0))
+        );
+        // issued long ago, so it's already expired
+        assert!(token.needs_refresh());
+    }
+
+    #[test]
+    fn refreshed_keeps_the_refresh_token() {
+        let token = token(None).refreshed(response(None, Some(7200)));
+        assert_eq!(token.refresh_token.as_deref(), Some("refresh"));
+        assert_eq!(token.client_id, "id");
+    }
+
+    #[test]
+    fn refreshed_without_expires_in() {
+        let token = token(Some(Utc::now())).refreshed(response(None, None));
+        assert_eq!(token.expires_at, None);
+        assert!(!token.needs_refresh());
+    }
 }
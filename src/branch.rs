@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+use crate::commit::Commit;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Branch {
+    pub name: String,
+    pub merged: bool,
+    pub protected: bool,
+    pub default: bool,
+    pub developers_can_push: bool,
+    pub developers_can_merge: bool,
+    pub can_push: bool,
+    pub web_url: String,
+    pub commit: Commit,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ListBranchesOption {
+    pub search: Option<String>,
+    pub regex: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AccessLevel {
+    pub id: Option<i64>,
+    pub access_level: i64,
+    pub access_level_description: String,
+    pub user_id: Option<i64>,
+    pub group_id: Option<i64>,
+    pub deploy_key_id: Option<i64>,
+}
+
+impl AccessLevel {
+    pub const NO_ACCESS: i64 = 0;
+    pub const DEVELOPER: i64 = 30;
+    pub const MAINTAINER: i64 = 40;
+    pub const ADMIN: i64 = 60;
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ProtectedBranch {
+    pub id: i64,
+    pub name: String,
+    pub push_access_levels: Vec<AccessLevel>,
+    pub merge_access_levels: Vec<AccessLevel>,
+    #[serde(default)]
+    pub unprotect_access_levels: Vec<AccessLevel>,
+    #[serde(default)]
+    pub allow_force_push: bool,
+    #[serde(default)]
+    pub code_owner_approval_required: bool,
+}
+
+/// A user, group, deploy key or role allowed to push/merge/unprotect
+#[skip_serializing_none]
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct AccessLevelOption {
+    /// Id of an existing access level, used to update or remove it
+    pub id: Option<i64>,
+    pub access_level: Option<i64>,
+    pub user_id: Option<i64>,
+    pub group_id: Option<i64>,
+    pub deploy_key_id: Option<i64>,
+    /// Remove the existing access level `id`
+    #[serde(rename = "_destroy")]
+    pub destroy: Option<bool>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ProtectBranchOption {
+    /// Branch name or wildcard, e.g. `release/*`
+    pub name: String,
+    pub push_access_level: Option<i64>,
+    pub merge_access_level: Option<i64>,
+    pub unprotect_access_level: Option<i64>,
+    pub allow_force_push: Option<bool>,
+    pub allowed_to_push: Option<Vec<AccessLevelOption>>,
+    pub allowed_to_merge: Option<Vec<AccessLevelOption>>,
+    pub allowed_to_unprotect: Option<Vec<AccessLevelOption>>,
+    pub code_owner_approval_required: Option<bool>,
+}
+
+impl ProtectBranchOption {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct UpdateProtectedBranchOption {
+    pub allow_force_push: Option<bool>,
+    pub allowed_to_push: Option<Vec<AccessLevelOption>>,
+    pub allowed_to_merge: Option<Vec<AccessLevelOption>>,
+    pub allowed_to_unprotect: Option<Vec<AccessLevelOption>>,
+    pub code_owner_approval_required: Option<bool>,
+}
@@ -1,12 +1,25 @@
+use std::{env, sync::Arc};
+
 use reqwest::Client;
-use url::Url;
+use url::{Position, Url};
 
-use crate::{auth::Auth, client::Gritlab, config::Config, Result};
+use crate::{
+    auth::{Auth, OAuth2Token, TokenRefreshCallback},
+    client::Gritlab,
+    config::Config,
+    ratelimit::RateLimitPolicy,
+    retry::RetryPolicy,
+    Result,
+};
 
 pub struct GritlabBuilder {
     scheme: String,
     host: String,
     token: Auth,
+    retry: RetryPolicy,
+    rate_limit: RateLimitPolicy,
+    per_page: Option<u32>,
+    on_token_refresh: Option<TokenRefreshCallback>,
     cli: Option<Client>,
 }
 
@@ -17,10 +30,37 @@ impl GritlabBuilder {
             scheme: "https".to_string(),
             host: host.into(),
             token: Auth::None,
+            retry: RetryPolicy::default(),
+            rate_limit: RateLimitPolicy::default(),
+            per_page: None,
+            on_token_refresh: None,
             cli: None,
         }
     }
 
+    /// Create a builder for the gitlab instance running the current CI job, which
+    /// authenticates with `CI_JOB_TOKEN`.
+    ///
+    /// The server is read from `CI_API_V4_URL`, falling back to `CI_SERVER_URL`.
+    pub fn from_ci_env() -> Result<Self> {
+        let server_url = match env::var("CI_API_V4_URL") {
+            Ok(api_url) => api_url
+                .trim_end_matches('/')
+                .trim_end_matches("/api/v4")
+                .to_string(),
+            Err(_) => env::var("CI_SERVER_URL")?,
+        };
+        let server_url = Url::parse(&server_url)?;
+        let host =
+            server_url[Position::BeforeHost..Position::AfterPath].trim_end_matches('/');
+
+        let mut builder = Self::new(host);
+        builder
+            .scheme(server_url.scheme())
+            .job_token(env::var("CI_JOB_TOKEN")?);
+        Ok(builder)
+    }
+
     /// Switch to an insecure protocol (http instead of https).
     pub fn insecure(&mut self) -> &mut Self {
         self.scheme = "http".to_string();
@@ -39,6 +79,69 @@ impl GritlabBuilder {
         self
     }
 
+    /// Switch to anonymous access, clearing any credentials configured before. It only
+    /// works for public resources, e.g. `search_repos` and `get_repo` on public
+    /// projects
+    pub fn anonymous(&mut self) -> &mut Self {
+        self.token = Auth::None;
+        self
+    }
+
+    /// Switch to using the job token of a running CI job (`CI_JOB_TOKEN`)
+    pub fn job_token(&mut self, token: impl Into<String>) -> &mut Self {
+        self.token = Auth::JobToken(token.into());
+        self
+    }
+
+    /// Switch to using a deploy token
+    pub fn deploy_token(
+        &mut self,
+        username: impl Into<String>,
+        token: impl Into<String>,
+    ) -> &mut Self {
+        self.token = Auth::DeployToken {
+            username: username.into(),
+            token: token.into(),
+        };
+        self
+    }
+
+    /// Switch to using an OAuth2 token, which is refreshed automatically before it
+    /// expires
+    pub fn oauth2(&mut self, token: OAuth2Token) -> &mut Self {
+        self.token = Auth::OAuth2(token);
+        self
+    }
+
+    /// Set the callback invoked with the new token after the oauth2 token has been
+    /// refreshed, e.g. to persist the rotated refresh token
+    pub fn on_token_refresh<F>(&mut self, callback: F) -> &mut Self
+    where
+        F: Fn(&OAuth2Token) + Send + Sync + 'static,
+    {
+        self.on_token_refresh = Some(TokenRefreshCallback(Arc::new(callback)));
+        self
+    }
+
+    /// Set the policy to retry the requests failed with transient errors
+    pub fn retry_policy(&mut self, retry: RetryPolicy) -> &mut Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Set the policy to pace the requests by the rate limit gitlab reports
+    pub fn rate_limit_policy(&mut self, rate_limit: RateLimitPolicy) -> &mut Self {
+        self.rate_limit = rate_limit;
+        self
+    }
+
+    /// Set the items per page fetched by the `*_pages` and `*_stream` apis unless
+    /// the query sets its own `per_page`, gitlab allows at most 100
+    pub fn per_page(&mut self, per_page: u32) -> &mut Self {
+        self.per_page = Some(per_page);
+        self
+    }
+
     /// Use the specified reqwest client, avoid to establish new http connections
     pub fn cli(&mut self, cli: Client) -> &mut Self {
         self.cli = Some(cli);
@@ -53,12 +156,20 @@ impl GritlabBuilder {
             None => Client::new(),
         };
 
-        Ok(Gritlab::new(
+        let gritlab = Gritlab::new(
             Config {
                 base_url,
                 token: self.token.clone(),
+                retry: self.retry.clone(),
+                rate_limit: self.rate_limit.clone(),
+                per_page: self.per_page,
             },
             cli,
-        ))
+        );
+
+        Ok(match &self.on_token_refresh {
+            Some(callback) => gritlab.with_token_refresh_callback(callback.clone()),
+            None => gritlab,
+        })
     }
 }
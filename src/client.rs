@@ -1,17 +1,79 @@
-use http::Method;
-use serde::de::DeserializeOwned;
-use std::sync::{Arc, RwLock};
+use bytes::Bytes;
+use futures::{stream, Stream, TryStreamExt};
+use http::{Method, StatusCode};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+use tokio::{sync::Mutex, time::sleep};
 use url::Url;
 
-use reqwest::{Client, RequestBuilder};
+use reqwest::{
+    header::{HeaderName, HeaderValue, RANGE},
+    Client, RequestBuilder, Response,
+};
 
 use crate::{
+    approval::{
+        ApprovalConfig, ApprovalRule, ApprovalState, ApproveOption,
+        CreateApprovalRuleOption, MergeRequestApprovals, UpdateApprovalConfigOption,
+        UpdateApprovalRuleOption,
+    },
+    auth::{Auth, TokenRefreshCallback},
+    branch::{
+        Branch, ListBranchesOption, ProtectBranchOption, ProtectedBranch,
+        UpdateProtectedBranchOption,
+    },
     builder::GritlabBuilder,
-    commit::{Commit, ListCommitsOption},
+    commit::{
+        CherryPickCommitOption, Commit, CommitComment, CommitRef, CommitResult,
+        CommitSignature, CreateCommitCommentOption, CreateCommitOption, Diff,
+        ListCommitRefsOption, ListCommitsOption, RevertCommitOption,
+    },
     config::Config,
-    hook::{CreateHookOption, Hook},
+    error::ApiError,
+    file::{
+        BlameFileOption, BlameRange, CreateFileOption, DeleteFileOption, FileCommit,
+        FileMeta, RepoFile, UpdateFileOption,
+    },
+    hook::{
+        CreateGroupHookOption, CreateHookOption, CreateSystemHookOption,
+        EditGroupHookOption, EditHookOption, EnsuredHook, GroupHook, Hook, HookAction,
+        SystemHook,
+    },
+    issue::{
+        CreateIssueLinkOption, CreateIssueOption, EditIssueOption, Issue, IssueLink,
+        LinkedIssue, ListIssuesOption,
+    },
+    job::{Job, ListJobsOption, PlayJobOption},
+    merge_request::{
+        AcceptMergeRequestOption, CreateMergeRequestOption, ListMergeRequestsOption,
+        MergeRequest, MergeRequestChanges, RebaseStatus, TimeStats,
+        UpdateMergeRequestOption,
+    },
+    note::{
+        CreateDiscussionOption, CreateNoteOption, Discussion, ListNotesOption, Note,
+        Noteable, UpdateNoteOption,
+    },
+    pagination::{list_url, Pages},
+    pipeline::{
+        CreatePipelineOption, ListPipelinesOption, Pipeline, PipelineVariable,
+        TestReportSummary, TriggerPipelineOption,
+    },
+    ratelimit::{RateLimitStatus, RateLimiter},
+    release::{
+        CreateReleaseLinkOption, CreateReleaseOption, ListReleasesOption, Release,
+        ReleaseLink, UpdateReleaseLinkOption, UpdateReleaseOption,
+    },
     repo::{ListProjectsOption, Repository},
+    repository::{
+        ArchiveOption, ChangelogNotes, CommitChangelogOption, Compare, CompareOption,
+        Contributor, GenerateChangelogOption, ListContributorsOption, ListTreeOption,
+        TreeNode,
+    },
     status::{CreateStatusOption, Status},
+    tag::{CreateTagOption, ListTagsOption, ProtectTagOption, ProtectedTag, Tag},
     user::User,
     Error, Result,
 };
@@ -20,6 +82,10 @@ use crate::{
 pub struct Gritlab {
     conf: Arc<RwLock<Config>>,
     cli: Client,
+    limiter: Arc<RateLimiter>,
+    refresh_lock: Arc<Mutex<()>>,
+    on_token_refresh: Option<TokenRefreshCallback>,
+    retry_non_idempotent: bool,
 }
 
 impl Gritlab {
@@ -31,13 +97,41 @@ impl Gritlab {
         Self {
             conf: Arc::new(RwLock::new(conf)),
             cli,
+            limiter: Arc::new(RateLimiter::new()),
+            refresh_lock: Arc::new(Mutex::new(())),
+            on_token_refresh: None,
+            retry_non_idempotent: false,
+        }
+    }
+
+    /// A client sharing the config of this one, which also retries the requests
+    /// that aren't safe to repeat in general. Use it for the calls known to be
+    /// harmless to repeat, e.g. `cli.retry_non_idempotent().create_status(..)`.
+    pub fn retry_non_idempotent(&self) -> Self {
+        Self {
+            retry_non_idempotent: true,
+            ..self.clone()
         }
     }
 
+    /// Set the callback invoked after the oauth2 token has been refreshed
+    pub fn with_token_refresh_callback(
+        mut self,
+        callback: TokenRefreshCallback,
+    ) -> Self {
+        self.on_token_refresh = Some(callback);
+        self
+    }
+
     pub fn r_conf(&self) -> Result<Config> {
         Ok(self.conf.read()?.clone())
     }
 
+    /// The rate limit observed from the latest response, shared by all the clones
+    pub fn rate_limit(&self) -> Option<RateLimitStatus> {
+        self.limiter.status()
+    }
+
     pub fn abs_url(&self, rel_url: &str) -> Result<Url> {
         Ok(self.r_conf()?.base_url.join(rel_url)?)
     }
@@ -46,22 +140,194 @@ impl Gritlab {
         Ok(self.abs_url("api/v4/")?.join(rel_url)?)
     }
 
-    pub fn headers(&self) -> Result<(String, String)> {
-        // TODO: auto refresh
-        self.r_conf()?.token.headers()
+    /// The auth header to send, `None` when no credentials are configured
+    pub fn headers(&self) -> Result<Option<(String, String)>> {
+        Ok(self.r_conf()?.token.headers())
+    }
+
+    /// Refresh the oauth2 access token through `/oauth/token` and write the new token
+    /// back into the config
+    pub async fn refresh_token(&self) -> Result<()> {
+        self.refresh_oauth2(true).await.map(|_| ())
+    }
+
+    /// Refresh the oauth2 token if it's forced or about to expire, returns whether the
+    /// token has changed. Concurrent refreshes are coalesced into one.
+    async fn refresh_oauth2(&self, force: bool) -> Result<bool> {
+        match self.r_conf()?.token {
+            Auth::OAuth2(token) if force || token.needs_refresh() => {
+                self.refresh_stale_oauth2(&token.access_token).await
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Refresh the oauth2 token unless the `stale` access token has already been
+    /// replaced by another task
+    async fn refresh_stale_oauth2(&self, stale: &str) -> Result<bool> {
+        let _guard = self.refresh_lock.lock().await;
+        let token = match self.r_conf()?.token {
+            Auth::OAuth2(token) => token,
+            _ => return Ok(false),
+        };
+        if token.access_token != stale {
+            // refreshed by another task while waiting for the lock
+            return Ok(true);
+        }
+
+        let url = self.abs_url("oauth/token")?;
+        let req = self.cli.post(url).form(&token.refresh_form()?).build()?;
+        let (method, url) = (req.method().clone(), req.url().clone());
+        let resp = self.cli.execute(req).await?;
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await?;
+            return Err(Error::from(ApiError::new(status, method, url, body))
+                .context("refresh oauth2 token failed"));
+        }
+
+        let token = token.refreshed(resp.json().await?);
+        self.conf.write()?.token = Auth::OAuth2(token.clone());
+        if let Some(callback) = &self.on_token_refresh {
+            (callback.0)(&token);
+        }
+        Ok(true)
     }
 
     pub fn request(&self, method: Method, rel_url: &str) -> Result<RequestBuilder> {
-        if rel_url.starts_with("/") {
+        if rel_url.starts_with('/') {
             return Err(Error::Other("rel_url shouldn't starts_with /".to_string()));
         }
-        let url = self.api_url(rel_url)?;
-        let auth_header = self.headers()?;
+        self.request_url(method, self.api_url(rel_url)?)
+    }
 
-        Ok(self
-            .cli
-            .request(method, url)
-            .header(auth_header.0, auth_header.1))
+    /// Build a request to an absolute url, e.g. the `next` link of a paginated api.
+    /// The credentials are attached by [`Gritlab::send`].
+    pub fn request_url(&self, method: Method, url: Url) -> Result<RequestBuilder> {
+        Ok(self.cli.request(method, url))
+    }
+
+    /// Send the request with the current credentials, a non-success response is
+    /// turned into [`Error::GitlabError`].
+    ///
+    /// Transient failures are retried according to the configured
+    /// [`RetryPolicy`](crate::retry::RetryPolicy). With oauth2, a `401` refreshes the
+    /// token and retries once.
+    pub async fn send(&self, req: RequestBuilder) -> Result<Response> {
+        let mut req = req.build()?;
+        self.refresh_oauth2(false).await?;
+
+        let conf = self.r_conf()?;
+        let policy = conf.retry;
+        let retryable =
+            self.retry_non_idempotent || policy.should_retry_method(req.method());
+
+        let mut attempt = 1;
+        let mut reauthenticated = false;
+        loop {
+            // read the credentials right before each attempt, another task may have
+            // refreshed them in between
+            let auth = self.r_conf()?.token;
+            if let Some((name, value)) = auth.headers() {
+                let name = HeaderName::from_bytes(name.as_bytes())
+                    .map_err(|e| Error::Other(e.to_string()))?;
+                let value = HeaderValue::from_str(&value)
+                    .map_err(|e| Error::Other(e.to_string()))?;
+                req.headers_mut().insert(name, value);
+            }
+            let stale_oauth2 = match &auth {
+                Auth::OAuth2(token)
+                    if !reauthenticated && token.refresh_token.is_some() =>
+                {
+                    Some(token.access_token.clone())
+                }
+                _ => None,
+            };
+
+            let (method, url) = (req.method().clone(), req.url().clone());
+            let retry_req = match retryable && attempt < policy.max_attempts
+                || stale_oauth2.is_some()
+            {
+                true => req.try_clone(),
+                false => None,
+            };
+
+            self.limiter.acquire(&conf.rate_limit).await;
+            let resp = self.cli.execute(req).await;
+            if let Ok(resp) = &resp {
+                self.limiter.observe(resp.headers());
+            }
+
+            let (delay, retry_req) = match resp {
+                Ok(resp) if resp.status().is_success() => return Ok(resp),
+                Ok(resp) => match (retry_req, stale_oauth2) {
+                    (Some(retry_req), Some(stale))
+                        if resp.status() == StatusCode::UNAUTHORIZED =>
+                    {
+                        // the token may have been revoked or expired early
+                        self.refresh_stale_oauth2(&stale).await?;
+                        reauthenticated = true;
+                        req = retry_req;
+                        continue;
+                    }
+                    (Some(retry_req), _)
+                        if retryable
+                            && attempt < policy.max_attempts
+                            && policy.should_retry_status(resp.status()) =>
+                    {
+                        (
+                            policy
+                                .delay(attempt, Some((resp.status(), resp.headers()))),
+                            retry_req,
+                        )
+                    }
+                    _ => {
+                        let status = resp.status();
+                        let body = resp.text().await?;
+                        let err = Error::from(ApiError::new(status, method, url, body));
+                        if status == StatusCode::UNAUTHORIZED && auth.is_anonymous() {
+                            return Err(err.context("no credentials are configured"));
+                        }
+                        return Err(err);
+                    }
+                },
+                Err(err) => match retry_req {
+                    Some(retry_req)
+                        if retryable
+                            && attempt < policy.max_attempts
+                            && (err.is_connect() || err.is_timeout()) =>
+                    {
+                        (policy.delay(attempt, None), retry_req)
+                    }
+                    _ => return Err(err.into()),
+                },
+            };
+
+            sleep(delay).await;
+            req = retry_req;
+            attempt += 1;
+        }
+    }
+
+    /// Create a lazy page iterator over the list api at `rel_url`
+    pub fn pages<T, Q>(
+        &self,
+        rel_url: &str,
+        query: &Q,
+        err_mes: &str,
+    ) -> Result<Pages<T>>
+    where
+        T: DeserializeOwned,
+        Q: Serialize + ?Sized,
+    {
+        let url = list_url(self.api_url(rel_url)?, query)?;
+        let pages = Pages::new(self.clone(), url.clone(), err_mes);
+        Ok(match self.r_conf()?.per_page {
+            Some(per_page) if !url.query_pairs().any(|(k, _)| k == "per_page") => {
+                pages.per_page(per_page)
+            }
+            _ => pages,
+        })
     }
 }
 
@@ -73,7 +339,8 @@ impl Gritlab {
 
     /// Get the user who owns the auth_token
     pub async fn current_user(&self) -> Result<User> {
-        let resp = self.request(Method::GET, "user")?.send().await?;
+        let req = self.request(Method::GET, "user")?;
+        let resp = self.send(req).await;
         resp_json(resp, "get user failed").await
     }
 
@@ -81,35 +348,77 @@ impl Gritlab {
     // Repository related apis
     // ===============================================
 
-    /// List all the repos which the user has permission to
+    /// List the repos which the user has permission to (first page only, see
+    /// [`Gritlab::list_repos_pages`] to iterate over all of them)
     pub async fn list_repos(
         &self,
         opt: Option<ListProjectsOption>,
     ) -> Result<Vec<Repository>> {
-        let resp = self
-            .request(Method::GET, "projects")?
-            .query(&opt)
-            .send()
-            .await?;
+        let req = self.request(Method::GET, "projects")?.query(&opt);
+        let resp = self.send(req).await;
         resp_json(resp, "list repos failed").await
     }
 
-    /// Search repos by query
+    /// Iterate over all the repos which the user has permission to page by page
+    pub fn list_repos_pages(
+        &self,
+        opt: Option<ListProjectsOption>,
+    ) -> Result<Pages<Repository>> {
+        self.pages("projects", &opt, "list repos failed")
+    }
+
+    /// Stream all the repos which the user has permission to.
+    ///
+    /// Keyset pagination (`pagination=keyset`) is used when ordering by `id`, so that
+    /// deep pages stay cheap for gitlab. Without any order, the repos are ordered by
+    /// `id desc`, which matches the `created_at desc` default of gitlab. Other orders
+    /// fall back to offset pagination.
+    pub fn list_repos_stream(
+        &self,
+        opt: Option<ListProjectsOption>,
+    ) -> Result<impl Stream<Item = Result<Repository>>> {
+        let mut opt = opt.unwrap_or_default();
+        if opt.pagination.is_none() {
+            match (opt.order_by.as_deref(), opt.sort.as_deref()) {
+                (Some("id"), _) => opt.pagination = Some("keyset".to_string()),
+                (None, None) => {
+                    opt.pagination = Some("keyset".to_string());
+                    opt.order_by = Some("id".to_string());
+                    opt.sort = Some("desc".to_string());
+                }
+                _ => {}
+            }
+        }
+        Ok(self.list_repos_pages(Some(opt))?.into_stream())
+    }
+
+    /// Search repos by query (first page only)
     pub async fn search_repos(&self, query: &str) -> Result<Vec<Repository>> {
-        let resp = self
+        let req = self
             .request(Method::GET, "projects")?
-            .query(&[("search", query)])
-            .send()
-            .await?;
+            .query(&[("search", query)]);
+        let resp = self.send(req).await;
         resp_json(resp, "search repos failed").await
     }
 
+    /// Iterate over the repos matching the query page by page
+    pub fn search_repos_pages(&self, query: &str) -> Result<Pages<Repository>> {
+        self.pages("projects", &[("search", query)], "search repos failed")
+    }
+
+    /// Stream the repos matching the query
+    pub fn search_repos_stream(
+        &self,
+        query: &str,
+    ) -> Result<impl Stream<Item = Result<Repository>>> {
+        Ok(self.search_repos_pages(query)?.into_stream())
+    }
+
     /// Get the specified repo
     pub async fn get_repo(&self, owner: &str, repo: &str) -> Result<Repository> {
-        let resp = self
-            .request(Method::GET, &format!("projects/{}", repo_path(owner, repo)))?
-            .send()
-            .await?;
+        let req =
+            self.request(Method::GET, &format!("projects/{}", repo_path(owner, repo)))?;
+        let resp = self.send(req).await;
         resp_json(resp, "get repo failed").await
     }
 
@@ -120,137 +429,4244 @@ impl Gritlab {
         repo: &str,
         opt: &CreateHookOption,
     ) -> Result<Hook> {
-        let resp = self
+        let req = self
             .request(
                 Method::POST,
                 &format!("projects/{}/hooks", repo_path(owner, repo)),
             )?
-            .json(opt)
-            .send()
-            .await?;
+            .json(opt);
+        let resp = self.send(req).await;
         resp_json(resp, "create hook failed").await
     }
 
     /// Delete a webhook
     pub async fn delete_hook(&self, owner: &str, repo: &str, id: i64) -> Result<()> {
-        let resp = self
+        let req = self.request(
+            Method::DELETE,
+            &format!("projects/{}/hooks/{}", repo_path(owner, repo), id),
+        )?;
+        let resp = self.send(req).await;
+        check_success(resp, &format!("delete hook-{} failed", id)).await
+    }
+
+    /// List webhooks of a repo (first page only)
+    pub async fn list_hooks(&self, owner: &str, repo: &str) -> Result<Vec<Hook>> {
+        let req = self.request(
+            Method::GET,
+            &format!("projects/{}/hooks", repo_path(owner, repo)),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(resp, "list repo hooks failed").await
+    }
+
+    /// Iterate over the webhooks of a repo page by page
+    pub fn list_hooks_pages(&self, owner: &str, repo: &str) -> Result<Pages<Hook>> {
+        self.pages(
+            &format!("projects/{}/hooks", repo_path(owner, repo)),
+            &(),
+            "list repo hooks failed",
+        )
+    }
+
+    /// Stream the webhooks of a repo
+    pub fn list_hooks_stream(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> Result<impl Stream<Item = Result<Hook>>> {
+        Ok(self.list_hooks_pages(owner, repo)?.into_stream())
+    }
+
+    /// Get the specified webhook
+    pub async fn get_hook(&self, owner: &str, repo: &str, id: i64) -> Result<Hook> {
+        let req = self.request(
+            Method::GET,
+            &format!("projects/{}/hooks/{}", repo_path(owner, repo), id),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("get hook-{} failed", id)).await
+    }
+
+    /// Edit a webhook, only the settings set in `opt` are updated and the fields left
+    /// to `None` are unchanged
+    pub async fn edit_hook(
+        &self,
+        owner: &str,
+        repo: &str,
+        id: i64,
+        opt: &EditHookOption,
+    ) -> Result<Hook> {
+        let req = self
             .request(
-                Method::DELETE,
+                Method::PUT,
                 &format!("projects/{}/hooks/{}", repo_path(owner, repo), id),
             )?
-            .send()
-            .await?;
-        check_success(resp, &format!("delete hook-{} failed", id)).await
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("edit hook-{} failed", id)).await
     }
 
-    /// List webhooks of a repo
-    pub async fn list_hooks(&self, owner: &str, repo: &str) -> Result<Vec<Hook>> {
-        let resp = self
+    /// Send a test event to a webhook, `trigger` is the event flag to test, e.g.
+    /// `push_events` or `merge_requests_events`
+    pub async fn test_hook(
+        &self,
+        owner: &str,
+        repo: &str,
+        id: i64,
+        trigger: &str,
+    ) -> Result<()> {
+        let req = self.request(
+            Method::POST,
+            &format!(
+                "projects/{}/hooks/{}/test/{}",
+                repo_path(owner, repo),
+                id,
+                trigger
+            ),
+        )?;
+        let resp = self.send(req).await;
+        check_success(resp, &format!("test {} of hook-{} failed", trigger, id)).await
+    }
+
+    /// Set a custom header sent by a webhook
+    pub async fn set_hook_custom_header(
+        &self,
+        owner: &str,
+        repo: &str,
+        id: i64,
+        key: &str,
+        value: &str,
+    ) -> Result<()> {
+        let req = self
             .request(
-                Method::GET,
-                &format!("projects/{}/hooks", repo_path(owner, repo)),
+                Method::PUT,
+                &format!(
+                    "projects/{}/hooks/{}/custom_headers/{}",
+                    repo_path(owner, repo),
+                    id,
+                    encode_path(key)
+                ),
             )?
-            .send()
-            .await?;
-        resp_json(resp, "list repo hooks failed").await
+            .json(&maplit::hashmap! { "value" => value });
+        let resp = self.send(req).await;
+        check_success(resp, &format!("set header {} of hook-{} failed", key, id)).await
+    }
+
+    /// Delete a custom header of a webhook
+    pub async fn delete_hook_custom_header(
+        &self,
+        owner: &str,
+        repo: &str,
+        id: i64,
+        key: &str,
+    ) -> Result<()> {
+        let req = self.request(
+            Method::DELETE,
+            &format!(
+                "projects/{}/hooks/{}/custom_headers/{}",
+                repo_path(owner, repo),
+                id,
+                encode_path(key)
+            ),
+        )?;
+        let resp = self.send(req).await;
+        check_success(
+            resp,
+            &format!("delete header {} of hook-{} failed", key, id),
+        )
+        .await
+    }
+
+    /// Set a variable substituted in the url of a webhook
+    pub async fn set_hook_url_variable(
+        &self,
+        owner: &str,
+        repo: &str,
+        id: i64,
+        key: &str,
+        value: &str,
+    ) -> Result<()> {
+        let req = self
+            .request(
+                Method::PUT,
+                &format!(
+                    "projects/{}/hooks/{}/url_variables/{}",
+                    repo_path(owner, repo),
+                    id,
+                    encode_path(key)
+                ),
+            )?
+            .json(&maplit::hashmap! { "value" => value });
+        let resp = self.send(req).await;
+        check_success(
+            resp,
+            &format!("set url variable {} of hook-{} failed", key, id),
+        )
+        .await
+    }
+
+    /// Delete a url variable of a webhook
+    pub async fn delete_hook_url_variable(
+        &self,
+        owner: &str,
+        repo: &str,
+        id: i64,
+        key: &str,
+    ) -> Result<()> {
+        let req = self.request(
+            Method::DELETE,
+            &format!(
+                "projects/{}/hooks/{}/url_variables/{}",
+                repo_path(owner, repo),
+                id,
+                encode_path(key)
+            ),
+        )?;
+        let resp = self.send(req).await;
+        check_success(
+            resp,
+            &format!("delete url variable {} of hook-{} failed", key, id),
+        )
+        .await
+    }
+
+    /// Make sure the repo has exactly one webhook with the url and settings of
+    /// `opt`: the hook is created or updated as needed, and the other hooks with
+    /// the same url are deleted.
+    ///
+    /// The settings left to `None` are reset to gitlab's defaults, and the url
+    /// variables and custom headers not set by `opt` are deleted. The hook is always
+    /// updated if `opt` sets a token, url variables or custom headers, as gitlab never
+    /// returns their values.
+    pub async fn ensure_hook(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: &CreateHookOption,
+    ) -> Result<EnsuredHook> {
+        let mut hooks: Vec<Hook> = self
+            .list_hooks_pages(owner, repo)?
+            .collect_all()
+            .await?
+            .into_iter()
+            .filter(|hook| hook.url == opt.url)
+            .collect();
+        // keep a hook which is already up to date, or else the oldest one
+        hooks.sort_by_key(|hook| (!opt.matches(hook), hook.id));
+
+        let mut hooks = hooks.into_iter();
+        let (hook, action) = match hooks.next() {
+            None => (
+                self.create_hook(owner, repo, opt).await?,
+                HookAction::Created,
+            ),
+            Some(hook) if !opt.has_secrets() && opt.matches(&hook) => {
+                (hook, HookAction::Unchanged)
+            }
+            Some(hook) => {
+                for key in opt.stale_url_variables(&hook) {
+                    self.delete_hook_url_variable(owner, repo, hook.id, key)
+                        .await?;
+                }
+                for key in opt.stale_custom_headers(&hook) {
+                    self.delete_hook_custom_header(owner, repo, hook.id, key)
+                        .await?;
+                }
+                let opt = opt.with_defaults().into();
+                (
+                    self.edit_hook(owner, repo, hook.id, &opt).await?,
+                    HookAction::Updated,
+                )
+            }
+        };
+
+        let mut removed_duplicates = Vec::new();
+        for duplicate in hooks {
+            self.delete_hook(owner, repo, duplicate.id).await?;
+            removed_duplicates.push(duplicate.id);
+        }
+
+        Ok(EnsuredHook {
+            hook,
+            action,
+            removed_duplicates,
+        })
     }
 
     // ===============================================
     // Commit related apis
     // ===============================================
 
-    /// List repo commits
+    /// List repo commits (first page only)
     pub async fn list_repo_commits(
         &self,
         owner: &str,
         repo: &str,
         opt: Option<ListCommitsOption>,
     ) -> Result<Vec<Commit>> {
-        let resp = self
+        let req = self
             .request(
                 Method::GET,
                 &format!("projects/{}/repository/commits", repo_path(owner, repo)),
             )?
-            .query(&opt)
-            .send()
-            .await?;
+            .query(&opt);
+        let resp = self.send(req).await;
         resp_json(resp, "failed to list repo commits").await
     }
 
-    /// List statuses of the commit
-    pub async fn list_statuses(
+    /// Iterate over the repo commits page by page
+    pub fn list_repo_commits_pages(
         &self,
         owner: &str,
         repo: &str,
-        commit: &str,
-    ) -> Result<Vec<Status>> {
-        let resp = self
+        opt: Option<ListCommitsOption>,
+    ) -> Result<Pages<Commit>> {
+        self.pages(
+            &format!("projects/{}/repository/commits", repo_path(owner, repo)),
+            &opt,
+            "failed to list repo commits",
+        )
+    }
+
+    /// Stream the repo commits
+    pub fn list_repo_commits_stream(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: Option<ListCommitsOption>,
+    ) -> Result<impl Stream<Item = Result<Commit>>> {
+        Ok(self
+            .list_repo_commits_pages(owner, repo, opt)?
+            .into_stream())
+    }
+
+    /// Create a commit with multiple file actions, applied atomically
+    pub async fn create_commit(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: &CreateCommitOption,
+    ) -> Result<Commit> {
+        let req = self
+            .request(
+                Method::POST,
+                &format!("projects/{}/repository/commits", repo_path(owner, repo)),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("create commit on {} failed", opt.branch)).await
+    }
+
+    /// Get a single commit, `sha` may also be a branch or tag name
+    pub async fn get_commit(
+        &self,
+        owner: &str,
+        repo: &str,
+        sha: &str,
+        stats: bool,
+    ) -> Result<Commit> {
+        let req = self
             .request(
                 Method::GET,
                 &format!(
-                    "projects/{}/repository/commits/{}/statuses",
+                    "projects/{}/repository/commits/{}",
                     repo_path(owner, repo),
-                    commit
+                    encode_path(sha)
                 ),
             )?
-            .send()
-            .await?;
-        resp_json(resp, &format!("get status of commit-{} failed", commit)).await
+            .query(&[("stats", stats)]);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("get commit-{} failed", sha)).await
     }
 
-    /// Create a status
-    pub async fn create_status(
+    /// Get the diff of a commit (first page only)
+    pub async fn get_commit_diff(
         &self,
         owner: &str,
         repo: &str,
-        commit: &str,
-        opt: &CreateStatusOption,
-    ) -> Result<Status> {
-        let resp = self
+        sha: &str,
+    ) -> Result<Vec<Diff>> {
+        let req = self.request(
+            Method::GET,
+            &format!(
+                "projects/{}/repository/commits/{}/diff",
+                repo_path(owner, repo),
+                encode_path(sha)
+            ),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("get diff of commit-{} failed", sha)).await
+    }
+
+    /// Iterate over the diff of a commit page by page
+    pub fn get_commit_diff_pages(
+        &self,
+        owner: &str,
+        repo: &str,
+        sha: &str,
+    ) -> Result<Pages<Diff>> {
+        self.pages(
+            &format!(
+                "projects/{}/repository/commits/{}/diff",
+                repo_path(owner, repo),
+                encode_path(sha)
+            ),
+            &(),
+            &format!("get diff of commit-{} failed", sha),
+        )
+    }
+
+    /// Stream the diff of a commit
+    pub fn get_commit_diff_stream(
+        &self,
+        owner: &str,
+        repo: &str,
+        sha: &str,
+    ) -> Result<impl Stream<Item = Result<Diff>>> {
+        Ok(self.get_commit_diff_pages(owner, repo, sha)?.into_stream())
+    }
+
+    /// List the comments of a commit (first page only)
+    pub async fn list_commit_comments(
+        &self,
+        owner: &str,
+        repo: &str,
+        sha: &str,
+    ) -> Result<Vec<CommitComment>> {
+        let req = self.request(
+            Method::GET,
+            &format!(
+                "projects/{}/repository/commits/{}/comments",
+                repo_path(owner, repo),
+                encode_path(sha)
+            ),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("list comments of commit-{} failed", sha)).await
+    }
+
+    /// Iterate over the comments of a commit page by page
+    pub fn list_commit_comments_pages(
+        &self,
+        owner: &str,
+        repo: &str,
+        sha: &str,
+    ) -> Result<Pages<CommitComment>> {
+        self.pages(
+            &format!(
+                "projects/{}/repository/commits/{}/comments",
+                repo_path(owner, repo),
+                encode_path(sha)
+            ),
+            &(),
+            &format!("list comments of commit-{} failed", sha),
+        )
+    }
+
+    /// Stream the comments of a commit
+    pub fn list_commit_comments_stream(
+        &self,
+        owner: &str,
+        repo: &str,
+        sha: &str,
+    ) -> Result<impl Stream<Item = Result<CommitComment>>> {
+        Ok(self
+            .list_commit_comments_pages(owner, repo, sha)?
+            .into_stream())
+    }
+
+    /// Comment on a commit, or on a line of its diff when `path` and `line` are set
+    pub async fn create_commit_comment(
+        &self,
+        owner: &str,
+        repo: &str,
+        sha: &str,
+        opt: &CreateCommitCommentOption,
+    ) -> Result<CommitComment> {
+        let req = self
             .request(
                 Method::POST,
-                &format!("projects/{}/statuses/{}", repo_path(owner, repo), commit),
+                &format!(
+                    "projects/{}/repository/commits/{}/comments",
+                    repo_path(owner, repo),
+                    encode_path(sha)
+                ),
             )?
-            .json(opt)
-            .send()
-            .await?;
-        resp_json(resp, &format!("create status of commit-{} failed", commit)).await
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("comment on commit-{} failed", sha)).await
     }
-}
 
-pub async fn resp_json<T>(resp: reqwest::Response, err_mes: &str) -> Result<T>
-where
-    T: DeserializeOwned,
-{
-    if !resp.status().is_success() {
-        Err(Error::GitlabError(format!(
-            "{}: [{}] {}",
-            err_mes,
-            resp.status(),
-            resp.text().await?
-        )))
-    } else {
-        Ok(resp.json::<T>().await?)
+    /// List the branches and tags containing a commit (first page only)
+    pub async fn list_commit_refs(
+        &self,
+        owner: &str,
+        repo: &str,
+        sha: &str,
+        opt: Option<ListCommitRefsOption>,
+    ) -> Result<Vec<CommitRef>> {
+        let req = self
+            .request(
+                Method::GET,
+                &format!(
+                    "projects/{}/repository/commits/{}/refs",
+                    repo_path(owner, repo),
+                    encode_path(sha)
+                ),
+            )?
+            .query(&opt);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("list refs of commit-{} failed", sha)).await
     }
-}
 
-pub async fn check_success(resp: reqwest::Response, err_mes: &str) -> Result<()> {
-    if !resp.status().is_success() {
-        Err(Error::GitlabError(format!(
-            "{}: [{}] {}",
-            err_mes,
-            resp.status(),
-            resp.text().await?
-        )))
-    } else {
-        Ok(())
+    /// Iterate over the branches and tags containing a commit page by page
+    pub fn list_commit_refs_pages(
+        &self,
+        owner: &str,
+        repo: &str,
+        sha: &str,
+        opt: Option<ListCommitRefsOption>,
+    ) -> Result<Pages<CommitRef>> {
+        self.pages(
+            &format!(
+                "projects/{}/repository/commits/{}/refs",
+                repo_path(owner, repo),
+                encode_path(sha)
+            ),
+            &opt,
+            &format!("list refs of commit-{} failed", sha),
+        )
     }
-}
 
-pub fn repo_path(owner: &str, repo: &str) -> String {
-    format!("{}%2F{}", owner, repo)
-}
+    /// Stream the branches and tags containing a commit
+    pub fn list_commit_refs_stream(
+        &self,
+        owner: &str,
+        repo: &str,
+        sha: &str,
+        opt: Option<ListCommitRefsOption>,
+    ) -> Result<impl Stream<Item = Result<CommitRef>>> {
+        Ok(self
+            .list_commit_refs_pages(owner, repo, sha, opt)?
+            .into_stream())
+    }
 
-pub async fn debug_resp(resp: reqwest::Response, start: usize) {
-    println!("{:#?}", &resp.text().await.unwrap()[start..start + 50]);
+    /// List the merge requests which introduced a commit (first page only)
+    pub async fn list_commit_merge_requests(
+        &self,
+        owner: &str,
+        repo: &str,
+        sha: &str,
+    ) -> Result<Vec<MergeRequest>> {
+        let req = self.request(
+            Method::GET,
+            &format!(
+                "projects/{}/repository/commits/{}/merge_requests",
+                repo_path(owner, repo),
+                encode_path(sha)
+            ),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(
+            resp,
+            &format!("list merge requests of commit-{} failed", sha),
+        )
+        .await
+    }
+
+    /// Iterate over the merge requests which introduced a commit page by page
+    pub fn list_commit_merge_requests_pages(
+        &self,
+        owner: &str,
+        repo: &str,
+        sha: &str,
+    ) -> Result<Pages<MergeRequest>> {
+        self.pages(
+            &format!(
+                "projects/{}/repository/commits/{}/merge_requests",
+                repo_path(owner, repo),
+                encode_path(sha)
+            ),
+            &(),
+            &format!("list merge requests of commit-{} failed", sha),
+        )
+    }
+
+    /// Stream the merge requests which introduced a commit
+    pub fn list_commit_merge_requests_stream(
+        &self,
+        owner: &str,
+        repo: &str,
+        sha: &str,
+    ) -> Result<impl Stream<Item = Result<MergeRequest>>> {
+        Ok(self
+            .list_commit_merge_requests_pages(owner, repo, sha)?
+            .into_stream())
+    }
+
+    /// Cherry-pick a commit onto a branch, only the dry run status is returned if
+    /// `opt.dry_run` is set
+    pub async fn cherry_pick_commit(
+        &self,
+        owner: &str,
+        repo: &str,
+        sha: &str,
+        opt: &CherryPickCommitOption,
+    ) -> Result<CommitResult> {
+        let req = self
+            .request(
+                Method::POST,
+                &format!(
+                    "projects/{}/repository/commits/{}/cherry_pick",
+                    repo_path(owner, repo),
+                    encode_path(sha)
+                ),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(
+            resp,
+            &format!("cherry-pick commit-{} onto {} failed", sha, opt.branch),
+        )
+        .await
+    }
+
+    /// Revert a commit on a branch, only the dry run status is returned if
+    /// `opt.dry_run` is set
+    pub async fn revert_commit(
+        &self,
+        owner: &str,
+        repo: &str,
+        sha: &str,
+        opt: &RevertCommitOption,
+    ) -> Result<CommitResult> {
+        let req = self
+            .request(
+                Method::POST,
+                &format!(
+                    "projects/{}/repository/commits/{}/revert",
+                    repo_path(owner, repo),
+                    encode_path(sha)
+                ),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(
+            resp,
+            &format!("revert commit-{} on {} failed", sha, opt.branch),
+        )
+        .await
+    }
+
+    /// Get the signature of a commit, fails with not found if it isn't signed
+    pub async fn get_commit_signature(
+        &self,
+        owner: &str,
+        repo: &str,
+        sha: &str,
+    ) -> Result<CommitSignature> {
+        let req = self.request(
+            Method::GET,
+            &format!(
+                "projects/{}/repository/commits/{}/signature",
+                repo_path(owner, repo),
+                encode_path(sha)
+            ),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("get signature of commit-{} failed", sha)).await
+    }
+
+    /// List statuses of the commit (first page only)
+    pub async fn list_statuses(
+        &self,
+        owner: &str,
+        repo: &str,
+        commit: &str,
+    ) -> Result<Vec<Status>> {
+        let req = self.request(
+            Method::GET,
+            &format!(
+                "projects/{}/repository/commits/{}/statuses",
+                repo_path(owner, repo),
+                commit
+            ),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("get status of commit-{} failed", commit)).await
+    }
+
+    /// Iterate over the statuses of the commit page by page
+    pub fn list_statuses_pages(
+        &self,
+        owner: &str,
+        repo: &str,
+        commit: &str,
+    ) -> Result<Pages<Status>> {
+        self.pages(
+            &format!(
+                "projects/{}/repository/commits/{}/statuses",
+                repo_path(owner, repo),
+                commit
+            ),
+            &(),
+            &format!("get status of commit-{} failed", commit),
+        )
+    }
+
+    /// Stream the statuses of the commit
+    pub fn list_statuses_stream(
+        &self,
+        owner: &str,
+        repo: &str,
+        commit: &str,
+    ) -> Result<impl Stream<Item = Result<Status>>> {
+        Ok(self.list_statuses_pages(owner, repo, commit)?.into_stream())
+    }
+
+    /// Create a status
+    pub async fn create_status(
+        &self,
+        owner: &str,
+        repo: &str,
+        commit: &str,
+        opt: &CreateStatusOption,
+    ) -> Result<Status> {
+        let req = self
+            .request(
+                Method::POST,
+                &format!("projects/{}/statuses/{}", repo_path(owner, repo), commit),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("create status of commit-{} failed", commit)).await
+    }
+
+    // ===============================================
+    // Merge request related apis
+    // ===============================================
+
+    /// List the merge requests the user has access to (first page only)
+    pub async fn list_merge_requests(
+        &self,
+        opt: Option<ListMergeRequestsOption>,
+    ) -> Result<Vec<MergeRequest>> {
+        let req = self.request(Method::GET, "merge_requests")?.query(&opt);
+        let resp = self.send(req).await;
+        resp_json(resp, "list merge requests failed").await
+    }
+
+    /// Iterate over the merge requests the user has access to page by page
+    pub fn list_merge_requests_pages(
+        &self,
+        opt: Option<ListMergeRequestsOption>,
+    ) -> Result<Pages<MergeRequest>> {
+        self.pages("merge_requests", &opt, "list merge requests failed")
+    }
+
+    /// Stream the merge requests the user has access to
+    pub fn list_merge_requests_stream(
+        &self,
+        opt: Option<ListMergeRequestsOption>,
+    ) -> Result<impl Stream<Item = Result<MergeRequest>>> {
+        Ok(self.list_merge_requests_pages(opt)?.into_stream())
+    }
+
+    /// List the merge requests of a group (first page only)
+    pub async fn list_group_merge_requests(
+        &self,
+        group: &str,
+        opt: Option<ListMergeRequestsOption>,
+    ) -> Result<Vec<MergeRequest>> {
+        let req = self
+            .request(
+                Method::GET,
+                &format!("groups/{}/merge_requests", group_path(group)),
+            )?
+            .query(&opt);
+        let resp = self.send(req).await;
+        resp_json(resp, "list group merge requests failed").await
+    }
+
+    /// Iterate over the merge requests of a group page by page
+    pub fn list_group_merge_requests_pages(
+        &self,
+        group: &str,
+        opt: Option<ListMergeRequestsOption>,
+    ) -> Result<Pages<MergeRequest>> {
+        self.pages(
+            &format!("groups/{}/merge_requests", group_path(group)),
+            &opt,
+            "list group merge requests failed",
+        )
+    }
+
+    /// Stream the merge requests of a group
+    pub fn list_group_merge_requests_stream(
+        &self,
+        group: &str,
+        opt: Option<ListMergeRequestsOption>,
+    ) -> Result<impl Stream<Item = Result<MergeRequest>>> {
+        Ok(self
+            .list_group_merge_requests_pages(group, opt)?
+            .into_stream())
+    }
+
+    /// List the merge requests of a repo (first page only)
+    pub async fn list_repo_merge_requests(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: Option<ListMergeRequestsOption>,
+    ) -> Result<Vec<MergeRequest>> {
+        let req = self
+            .request(
+                Method::GET,
+                &format!("projects/{}/merge_requests", repo_path(owner, repo)),
+            )?
+            .query(&opt);
+        let resp = self.send(req).await;
+        resp_json(resp, "list repo merge requests failed").await
+    }
+
+    /// Iterate over the merge requests of a repo page by page
+    pub fn list_repo_merge_requests_pages(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: Option<ListMergeRequestsOption>,
+    ) -> Result<Pages<MergeRequest>> {
+        self.pages(
+            &format!("projects/{}/merge_requests", repo_path(owner, repo)),
+            &opt,
+            "list repo merge requests failed",
+        )
+    }
+
+    /// Stream the merge requests of a repo
+    pub fn list_repo_merge_requests_stream(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: Option<ListMergeRequestsOption>,
+    ) -> Result<impl Stream<Item = Result<MergeRequest>>> {
+        Ok(self
+            .list_repo_merge_requests_pages(owner, repo, opt)?
+            .into_stream())
+    }
+
+    /// Get the specified merge request
+    pub async fn get_merge_request(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+    ) -> Result<MergeRequest> {
+        let req = self.request(
+            Method::GET,
+            &format!("projects/{}/merge_requests/{}", repo_path(owner, repo), iid),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("get merge request-{} failed", iid)).await
+    }
+
+    /// Create a merge request
+    pub async fn create_merge_request(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: &CreateMergeRequestOption,
+    ) -> Result<MergeRequest> {
+        let req = self
+            .request(
+                Method::POST,
+                &format!("projects/{}/merge_requests", repo_path(owner, repo)),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, "create merge request failed").await
+    }
+
+    /// Update a merge request
+    pub async fn update_merge_request(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+        opt: &UpdateMergeRequestOption,
+    ) -> Result<MergeRequest> {
+        let req = self
+            .request(
+                Method::PUT,
+                &format!("projects/{}/merge_requests/{}", repo_path(owner, repo), iid),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("update merge request-{} failed", iid)).await
+    }
+
+    /// Close a merge request
+    pub async fn close_merge_request(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+    ) -> Result<MergeRequest> {
+        let opt = UpdateMergeRequestOption {
+            state_event: Some("close".to_string()),
+            ..Default::default()
+        };
+        self.update_merge_request(owner, repo, iid, &opt).await
+    }
+
+    /// Reopen a closed merge request
+    pub async fn reopen_merge_request(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+    ) -> Result<MergeRequest> {
+        let opt = UpdateMergeRequestOption {
+            state_event: Some("reopen".to_string()),
+            ..Default::default()
+        };
+        self.update_merge_request(owner, repo, iid, &opt).await
+    }
+
+    /// Delete a merge request (admins and project owners only)
+    pub async fn delete_merge_request(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+    ) -> Result<()> {
+        let req = self.request(
+            Method::DELETE,
+            &format!("projects/{}/merge_requests/{}", repo_path(owner, repo), iid),
+        )?;
+        let resp = self.send(req).await;
+        check_success(resp, &format!("delete merge request-{} failed", iid)).await
+    }
+
+    /// Accept (merge) a merge request
+    pub async fn merge_merge_request(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+        opt: &AcceptMergeRequestOption,
+    ) -> Result<MergeRequest> {
+        let req = self
+            .request(
+                Method::PUT,
+                &format!(
+                    "projects/{}/merge_requests/{}/merge",
+                    repo_path(owner, repo),
+                    iid
+                ),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("merge merge request-{} failed", iid)).await
+    }
+
+    /// Cancel merging a merge request when its pipeline succeeds
+    pub async fn cancel_merge_when_pipeline_succeeds(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+    ) -> Result<MergeRequest> {
+        let req = self.request(
+            Method::POST,
+            &format!(
+                "projects/{}/merge_requests/{}/cancel_merge_when_pipeline_succeeds",
+                repo_path(owner, repo),
+                iid
+            ),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(
+            resp,
+            &format!("cancel merge when pipeline succeeds of mr-{} failed", iid),
+        )
+        .await
+    }
+
+    /// Rebase the source branch of a merge request onto its target branch, the
+    /// rebase runs asynchronously
+    pub async fn rebase_merge_request(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+        skip_ci: bool,
+    ) -> Result<RebaseStatus> {
+        let req = self
+            .request(
+                Method::PUT,
+                &format!(
+                    "projects/{}/merge_requests/{}/rebase",
+                    repo_path(owner, repo),
+                    iid
+                ),
+            )?
+            .query(&[("skip_ci", skip_ci)]);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("rebase merge request-{} failed", iid)).await
+    }
+
+    /// Get a merge request along with the diffs of its changed files
+    pub async fn get_merge_request_changes(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+    ) -> Result<MergeRequestChanges> {
+        let req = self.request(
+            Method::GET,
+            &format!(
+                "projects/{}/merge_requests/{}/changes",
+                repo_path(owner, repo),
+                iid
+            ),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(
+            resp,
+            &format!("get changes of merge request-{} failed", iid),
+        )
+        .await
+    }
+
+    /// List the diffs of a merge request (first page only)
+    pub async fn list_merge_request_diffs(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+    ) -> Result<Vec<Diff>> {
+        let req = self.request(
+            Method::GET,
+            &format!(
+                "projects/{}/merge_requests/{}/diffs",
+                repo_path(owner, repo),
+                iid
+            ),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("list diffs of merge request-{} failed", iid)).await
+    }
+
+    /// Iterate over the diffs of a merge request page by page
+    pub fn list_merge_request_diffs_pages(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+    ) -> Result<Pages<Diff>> {
+        self.pages(
+            &format!(
+                "projects/{}/merge_requests/{}/diffs",
+                repo_path(owner, repo),
+                iid
+            ),
+            &(),
+            &format!("list diffs of merge request-{} failed", iid),
+        )
+    }
+
+    /// Stream the diffs of a merge request
+    pub fn list_merge_request_diffs_stream(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+    ) -> Result<impl Stream<Item = Result<Diff>>> {
+        Ok(self
+            .list_merge_request_diffs_pages(owner, repo, iid)?
+            .into_stream())
+    }
+
+    /// List the commits of a merge request (first page only)
+    pub async fn list_merge_request_commits(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+    ) -> Result<Vec<Commit>> {
+        let req = self.request(
+            Method::GET,
+            &format!(
+                "projects/{}/merge_requests/{}/commits",
+                repo_path(owner, repo),
+                iid
+            ),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(
+            resp,
+            &format!("list commits of merge request-{} failed", iid),
+        )
+        .await
+    }
+
+    /// Iterate over the commits of a merge request page by page
+    pub fn list_merge_request_commits_pages(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+    ) -> Result<Pages<Commit>> {
+        self.pages(
+            &format!(
+                "projects/{}/merge_requests/{}/commits",
+                repo_path(owner, repo),
+                iid
+            ),
+            &(),
+            &format!("list commits of merge request-{} failed", iid),
+        )
+    }
+
+    /// Stream the commits of a merge request
+    pub fn list_merge_request_commits_stream(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+    ) -> Result<impl Stream<Item = Result<Commit>>> {
+        Ok(self
+            .list_merge_request_commits_pages(owner, repo, iid)?
+            .into_stream())
+    }
+
+    // ===============================================
+    // Note and discussion related apis
+    // ===============================================
+
+    /// List the notes of an issue, merge request or snippet (first page only)
+    pub async fn list_notes(
+        &self,
+        owner: &str,
+        repo: &str,
+        noteable: Noteable<'_>,
+        opt: Option<ListNotesOption>,
+    ) -> Result<Vec<Note>> {
+        let req = self
+            .request(
+                Method::GET,
+                &format!(
+                    "projects/{}/{}/notes",
+                    repo_path(owner, repo),
+                    noteable.path()
+                ),
+            )?
+            .query(&opt);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("list notes of {} failed", noteable.path())).await
+    }
+
+    /// Iterate over the notes of an issue, merge request or snippet page by page
+    pub fn list_notes_pages(
+        &self,
+        owner: &str,
+        repo: &str,
+        noteable: Noteable<'_>,
+        opt: Option<ListNotesOption>,
+    ) -> Result<Pages<Note>> {
+        self.pages(
+            &format!(
+                "projects/{}/{}/notes",
+                repo_path(owner, repo),
+                noteable.path()
+            ),
+            &opt,
+            &format!("list notes of {} failed", noteable.path()),
+        )
+    }
+
+    /// Stream the notes of an issue, merge request or snippet
+    pub fn list_notes_stream(
+        &self,
+        owner: &str,
+        repo: &str,
+        noteable: Noteable<'_>,
+        opt: Option<ListNotesOption>,
+    ) -> Result<impl Stream<Item = Result<Note>>> {
+        Ok(self
+            .list_notes_pages(owner, repo, noteable, opt)?
+            .into_stream())
+    }
+
+    /// Get the specified note
+    pub async fn get_note(
+        &self,
+        owner: &str,
+        repo: &str,
+        noteable: Noteable<'_>,
+        note_id: i64,
+    ) -> Result<Note> {
+        let req = self.request(
+            Method::GET,
+            &format!(
+                "projects/{}/{}/notes/{}",
+                repo_path(owner, repo),
+                noteable.path(),
+                note_id
+            ),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("get note-{} failed", note_id)).await
+    }
+
+    /// Create a note (comment) on an issue, merge request or snippet
+    pub async fn create_note(
+        &self,
+        owner: &str,
+        repo: &str,
+        noteable: Noteable<'_>,
+        opt: &CreateNoteOption,
+    ) -> Result<Note> {
+        let req = self
+            .request(
+                Method::POST,
+                &format!(
+                    "projects/{}/{}/notes",
+                    repo_path(owner, repo),
+                    noteable.path()
+                ),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("create note on {} failed", noteable.path())).await
+    }
+
+    /// Edit a note
+    pub async fn update_note(
+        &self,
+        owner: &str,
+        repo: &str,
+        noteable: Noteable<'_>,
+        note_id: i64,
+        opt: &UpdateNoteOption,
+    ) -> Result<Note> {
+        let req = self
+            .request(
+                Method::PUT,
+                &format!(
+                    "projects/{}/{}/notes/{}",
+                    repo_path(owner, repo),
+                    noteable.path(),
+                    note_id
+                ),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("update note-{} failed", note_id)).await
+    }
+
+    /// Delete a note
+    pub async fn delete_note(
+        &self,
+        owner: &str,
+        repo: &str,
+        noteable: Noteable<'_>,
+        note_id: i64,
+    ) -> Result<()> {
+        let req = self.request(
+            Method::DELETE,
+            &format!(
+                "projects/{}/{}/notes/{}",
+                repo_path(owner, repo),
+                noteable.path(),
+                note_id
+            ),
+        )?;
+        let resp = self.send(req).await;
+        check_success(resp, &format!("delete note-{} failed", note_id)).await
+    }
+
+    /// List the discussions of an issue, merge request, snippet or commit (first
+    /// page only)
+    pub async fn list_discussions(
+        &self,
+        owner: &str,
+        repo: &str,
+        noteable: Noteable<'_>,
+    ) -> Result<Vec<Discussion>> {
+        let req = self.request(
+            Method::GET,
+            &format!(
+                "projects/{}/{}/discussions",
+                repo_path(owner, repo),
+                noteable.path()
+            ),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(
+            resp,
+            &format!("list discussions of {} failed", noteable.path()),
+        )
+        .await
+    }
+
+    /// Iterate over the discussions of an issue, merge request, snippet or commit
+    /// page by page
+    pub fn list_discussions_pages(
+        &self,
+        owner: &str,
+        repo: &str,
+        noteable: Noteable<'_>,
+    ) -> Result<Pages<Discussion>> {
+        self.pages(
+            &format!(
+                "projects/{}/{}/discussions",
+                repo_path(owner, repo),
+                noteable.path()
+            ),
+            &(),
+            &format!("list discussions of {} failed", noteable.path()),
+        )
+    }
+
+    /// Stream the discussions of an issue, merge request, snippet or commit
+    pub fn list_discussions_stream(
+        &self,
+        owner: &str,
+        repo: &str,
+        noteable: Noteable<'_>,
+    ) -> Result<impl Stream<Item = Result<Discussion>>> {
+        Ok(self
+            .list_discussions_pages(owner, repo, noteable)?
+            .into_stream())
+    }
+
+    /// Get the specified discussion
+    pub async fn get_discussion(
+        &self,
+        owner: &str,
+        repo: &str,
+        noteable: Noteable<'_>,
+        discussion_id: &str,
+    ) -> Result<Discussion> {
+        let req = self.request(
+            Method::GET,
+            &format!(
+                "projects/{}/{}/discussions/{}",
+                repo_path(owner, repo),
+                noteable.path(),
+                discussion_id
+            ),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("get discussion-{} failed", discussion_id)).await
+    }
+
+    /// Start a discussion, pass a [`Position`](crate::note::Position) in the option
+    /// to comment inline on a merge request or commit diff
+    pub async fn create_discussion(
+        &self,
+        owner: &str,
+        repo: &str,
+        noteable: Noteable<'_>,
+        opt: &CreateDiscussionOption,
+    ) -> Result<Discussion> {
+        let req = self
+            .request(
+                Method::POST,
+                &format!(
+                    "projects/{}/{}/discussions",
+                    repo_path(owner, repo),
+                    noteable.path()
+                ),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(
+            resp,
+            &format!("create discussion on {} failed", noteable.path()),
+        )
+        .await
+    }
+
+    /// Resolve or unresolve a discussion of a merge request
+    pub async fn resolve_merge_request_discussion(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+        discussion_id: &str,
+        resolved: bool,
+    ) -> Result<Discussion> {
+        let req = self
+            .request(
+                Method::PUT,
+                &format!(
+                    "projects/{}/merge_requests/{}/discussions/{}",
+                    repo_path(owner, repo),
+                    iid,
+                    discussion_id
+                ),
+            )?
+            .query(&[("resolved", resolved)]);
+        let resp = self.send(req).await;
+        resp_json(
+            resp,
+            &format!("resolve discussion-{} failed", discussion_id),
+        )
+        .await
+    }
+
+    /// Reply to a discussion thread
+    pub async fn reply_to_discussion(
+        &self,
+        owner: &str,
+        repo: &str,
+        noteable: Noteable<'_>,
+        discussion_id: &str,
+        opt: &CreateNoteOption,
+    ) -> Result<Note> {
+        let req = self
+            .request(
+                Method::POST,
+                &format!(
+                    "projects/{}/{}/discussions/{}/notes",
+                    repo_path(owner, repo),
+                    noteable.path(),
+                    discussion_id
+                ),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(
+            resp,
+            &format!("reply to discussion-{} failed", discussion_id),
+        )
+        .await
+    }
+
+    /// Edit a note of a discussion thread, also used to resolve a single note
+    pub async fn update_discussion_note(
+        &self,
+        owner: &str,
+        repo: &str,
+        noteable: Noteable<'_>,
+        discussion_id: &str,
+        note_id: i64,
+        opt: &UpdateNoteOption,
+    ) -> Result<Note> {
+        let req = self
+            .request(
+                Method::PUT,
+                &format!(
+                    "projects/{}/{}/discussions/{}/notes/{}",
+                    repo_path(owner, repo),
+                    noteable.path(),
+                    discussion_id,
+                    note_id
+                ),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("update note-{} failed", note_id)).await
+    }
+
+    /// Delete a note of a discussion thread
+    pub async fn delete_discussion_note(
+        &self,
+        owner: &str,
+        repo: &str,
+        noteable: Noteable<'_>,
+        discussion_id: &str,
+        note_id: i64,
+    ) -> Result<()> {
+        let req = self.request(
+            Method::DELETE,
+            &format!(
+                "projects/{}/{}/discussions/{}/notes/{}",
+                repo_path(owner, repo),
+                noteable.path(),
+                discussion_id,
+                note_id
+            ),
+        )?;
+        let resp = self.send(req).await;
+        check_success(resp, &format!("delete note-{} failed", note_id)).await
+    }
+
+    // ===============================================
+    // Approval related apis
+    // ===============================================
+
+    /// Get the approval configuration of a repo
+    pub async fn get_repo_approval_config(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> Result<ApprovalConfig> {
+        let req = self.request(
+            Method::GET,
+            &format!("projects/{}/approvals", repo_path(owner, repo)),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(resp, "get repo approval config failed").await
+    }
+
+    /// Change the approval configuration of a repo
+    pub async fn update_repo_approval_config(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: &UpdateApprovalConfigOption,
+    ) -> Result<ApprovalConfig> {
+        let req = self
+            .request(
+                Method::POST,
+                &format!("projects/{}/approvals", repo_path(owner, repo)),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, "update repo approval config failed").await
+    }
+
+    /// List the approval rules of a repo (first page only)
+    pub async fn list_repo_approval_rules(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> Result<Vec<ApprovalRule>> {
+        let req = self.request(
+            Method::GET,
+            &format!("projects/{}/approval_rules", repo_path(owner, repo)),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(resp, "list repo approval rules failed").await
+    }
+
+    /// Iterate over the approval rules of a repo page by page
+    pub fn list_repo_approval_rules_pages(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> Result<Pages<ApprovalRule>> {
+        self.pages(
+            &format!("projects/{}/approval_rules", repo_path(owner, repo)),
+            &(),
+            "list repo approval rules failed",
+        )
+    }
+
+    /// Stream the approval rules of a repo
+    pub fn list_repo_approval_rules_stream(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> Result<impl Stream<Item = Result<ApprovalRule>>> {
+        Ok(self
+            .list_repo_approval_rules_pages(owner, repo)?
+            .into_stream())
+    }
+
+    /// Get the specified approval rule of a repo
+    pub async fn get_repo_approval_rule(
+        &self,
+        owner: &str,
+        repo: &str,
+        rule_id: i64,
+    ) -> Result<ApprovalRule> {
+        let req = self.request(
+            Method::GET,
+            &format!(
+                "projects/{}/approval_rules/{}",
+                repo_path(owner, repo),
+                rule_id
+            ),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("get approval rule-{} failed", rule_id)).await
+    }
+
+    /// Create an approval rule of a repo
+    pub async fn create_repo_approval_rule(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: &CreateApprovalRuleOption,
+    ) -> Result<ApprovalRule> {
+        let req = self
+            .request(
+                Method::POST,
+                &format!("projects/{}/approval_rules", repo_path(owner, repo)),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, "create repo approval rule failed").await
+    }
+
+    /// Update an approval rule of a repo
+    pub async fn update_repo_approval_rule(
+        &self,
+        owner: &str,
+        repo: &str,
+        rule_id: i64,
+        opt: &UpdateApprovalRuleOption,
+    ) -> Result<ApprovalRule> {
+        let req = self
+            .request(
+                Method::PUT,
+                &format!(
+                    "projects/{}/approval_rules/{}",
+                    repo_path(owner, repo),
+                    rule_id
+                ),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("update approval rule-{} failed", rule_id)).await
+    }
+
+    /// Delete an approval rule of a repo
+    pub async fn delete_repo_approval_rule(
+        &self,
+        owner: &str,
+        repo: &str,
+        rule_id: i64,
+    ) -> Result<()> {
+        let req = self.request(
+            Method::DELETE,
+            &format!(
+                "projects/{}/approval_rules/{}",
+                repo_path(owner, repo),
+                rule_id
+            ),
+        )?;
+        let resp = self.send(req).await;
+        check_success(resp, &format!("delete approval rule-{} failed", rule_id)).await
+    }
+
+    /// Get the approvals of a merge request
+    pub async fn get_merge_request_approvals(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+    ) -> Result<MergeRequestApprovals> {
+        let req = self.request(
+            Method::GET,
+            &format!(
+                "projects/{}/merge_requests/{}/approvals",
+                repo_path(owner, repo),
+                iid
+            ),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(
+            resp,
+            &format!("get approvals of merge request-{} failed", iid),
+        )
+        .await
+    }
+
+    /// Get the approval state of a merge request, i.e. its rules and who approved
+    /// them
+    pub async fn get_merge_request_approval_state(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+    ) -> Result<ApprovalState> {
+        let req = self.request(
+            Method::GET,
+            &format!(
+                "projects/{}/merge_requests/{}/approval_state",
+                repo_path(owner, repo),
+                iid
+            ),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(
+            resp,
+            &format!("get approval state of merge request-{} failed", iid),
+        )
+        .await
+    }
+
+    /// Approve a merge request as the current user
+    pub async fn approve_merge_request(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+        opt: &ApproveOption,
+    ) -> Result<MergeRequestApprovals> {
+        let req = self
+            .request(
+                Method::POST,
+                &format!(
+                    "projects/{}/merge_requests/{}/approve",
+                    repo_path(owner, repo),
+                    iid
+                ),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("approve merge request-{} failed", iid)).await
+    }
+
+    /// Withdraw the approval of the current user from a merge request
+    pub async fn unapprove_merge_request(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+    ) -> Result<()> {
+        let req = self.request(
+            Method::POST,
+            &format!(
+                "projects/{}/merge_requests/{}/unapprove",
+                repo_path(owner, repo),
+                iid
+            ),
+        )?;
+        let resp = self.send(req).await;
+        check_success(resp, &format!("unapprove merge request-{} failed", iid)).await
+    }
+
+    /// List the approval rules of a merge request
+    pub async fn list_merge_request_approval_rules(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+    ) -> Result<Vec<ApprovalRule>> {
+        let req = self.request(
+            Method::GET,
+            &format!(
+                "projects/{}/merge_requests/{}/approval_rules",
+                repo_path(owner, repo),
+                iid
+            ),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(
+            resp,
+            &format!("list approval rules of merge request-{} failed", iid),
+        )
+        .await
+    }
+
+    /// Create an approval rule of a merge request
+    pub async fn create_merge_request_approval_rule(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+        opt: &CreateApprovalRuleOption,
+    ) -> Result<ApprovalRule> {
+        let req = self
+            .request(
+                Method::POST,
+                &format!(
+                    "projects/{}/merge_requests/{}/approval_rules",
+                    repo_path(owner, repo),
+                    iid
+                ),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(
+            resp,
+            &format!("create approval rule of merge request-{} failed", iid),
+        )
+        .await
+    }
+
+    /// Update an approval rule of a merge request
+    pub async fn update_merge_request_approval_rule(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+        rule_id: i64,
+        opt: &UpdateApprovalRuleOption,
+    ) -> Result<ApprovalRule> {
+        let req = self
+            .request(
+                Method::PUT,
+                &format!(
+                    "projects/{}/merge_requests/{}/approval_rules/{}",
+                    repo_path(owner, repo),
+                    iid,
+                    rule_id
+                ),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("update approval rule-{} failed", rule_id)).await
+    }
+
+    /// Delete an approval rule of a merge request
+    pub async fn delete_merge_request_approval_rule(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+        rule_id: i64,
+    ) -> Result<()> {
+        let req = self.request(
+            Method::DELETE,
+            &format!(
+                "projects/{}/merge_requests/{}/approval_rules/{}",
+                repo_path(owner, repo),
+                iid,
+                rule_id
+            ),
+        )?;
+        let resp = self.send(req).await;
+        check_success(resp, &format!("delete approval rule-{} failed", rule_id)).await
+    }
+
+    // ===============================================
+    // Issue related apis
+    // ===============================================
+
+    /// List the issues the user has access to (first page only)
+    pub async fn list_issues(
+        &self,
+        opt: Option<ListIssuesOption>,
+    ) -> Result<Vec<Issue>> {
+        let req = self.request(Method::GET, "issues")?.query(&opt);
+        let resp = self.send(req).await;
+        resp_json(resp, "list issues failed").await
+    }
+
+    /// Iterate over the issues the user has access to page by page
+    pub fn list_issues_pages(
+        &self,
+        opt: Option<ListIssuesOption>,
+    ) -> Result<Pages<Issue>> {
+        self.pages("issues", &opt, "list issues failed")
+    }
+
+    /// Stream the issues the user has access to
+    pub fn list_issues_stream(
+        &self,
+        opt: Option<ListIssuesOption>,
+    ) -> Result<impl Stream<Item = Result<Issue>>> {
+        Ok(self.list_issues_pages(opt)?.into_stream())
+    }
+
+    /// List the issues of a group (first page only)
+    pub async fn list_group_issues(
+        &self,
+        group: &str,
+        opt: Option<ListIssuesOption>,
+    ) -> Result<Vec<Issue>> {
+        let req = self
+            .request(Method::GET, &format!("groups/{}/issues", group_path(group)))?
+            .query(&opt);
+        let resp = self.send(req).await;
+        resp_json(resp, "list group issues failed").await
+    }
+
+    /// Iterate over the issues of a group page by page
+    pub fn list_group_issues_pages(
+        &self,
+        group: &str,
+        opt: Option<ListIssuesOption>,
+    ) -> Result<Pages<Issue>> {
+        self.pages(
+            &format!("groups/{}/issues", group_path(group)),
+            &opt,
+            "list group issues failed",
+        )
+    }
+
+    /// Stream the issues of a group
+    pub fn list_group_issues_stream(
+        &self,
+        group: &str,
+        opt: Option<ListIssuesOption>,
+    ) -> Result<impl Stream<Item = Result<Issue>>> {
+        Ok(self.list_group_issues_pages(group, opt)?.into_stream())
+    }
+
+    /// List the issues of a repo (first page only)
+    pub async fn list_repo_issues(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: Option<ListIssuesOption>,
+    ) -> Result<Vec<Issue>> {
+        let req = self
+            .request(
+                Method::GET,
+                &format!("projects/{}/issues", repo_path(owner, repo)),
+            )?
+            .query(&opt);
+        let resp = self.send(req).await;
+        resp_json(resp, "list repo issues failed").await
+    }
+
+    /// Iterate over the issues of a repo page by page
+    pub fn list_repo_issues_pages(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: Option<ListIssuesOption>,
+    ) -> Result<Pages<Issue>> {
+        self.pages(
+            &format!("projects/{}/issues", repo_path(owner, repo)),
+            &opt,
+            "list repo issues failed",
+        )
+    }
+
+    /// Stream the issues of a repo
+    pub fn list_repo_issues_stream(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: Option<ListIssuesOption>,
+    ) -> Result<impl Stream<Item = Result<Issue>>> {
+        Ok(self.list_repo_issues_pages(owner, repo, opt)?.into_stream())
+    }
+
+    /// Get the specified issue
+    pub async fn get_issue(&self, owner: &str, repo: &str, iid: i64) -> Result<Issue> {
+        let req = self.request(
+            Method::GET,
+            &format!("projects/{}/issues/{}", repo_path(owner, repo), iid),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("get issue-{} failed", iid)).await
+    }
+
+    /// Create an issue
+    pub async fn create_issue(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: &CreateIssueOption,
+    ) -> Result<Issue> {
+        let req = self
+            .request(
+                Method::POST,
+                &format!("projects/{}/issues", repo_path(owner, repo)),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, "create issue failed").await
+    }
+
+    /// Edit an issue
+    pub async fn edit_issue(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+        opt: &EditIssueOption,
+    ) -> Result<Issue> {
+        let req = self
+            .request(
+                Method::PUT,
+                &format!("projects/{}/issues/{}", repo_path(owner, repo), iid),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("edit issue-{} failed", iid)).await
+    }
+
+    /// Close an issue
+    pub async fn close_issue(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+    ) -> Result<Issue> {
+        let opt = EditIssueOption {
+            state_event: Some("close".to_string()),
+            ..Default::default()
+        };
+        self.edit_issue(owner, repo, iid, &opt).await
+    }
+
+    /// Reopen a closed issue
+    pub async fn reopen_issue(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+    ) -> Result<Issue> {
+        let opt = EditIssueOption {
+            state_event: Some("reopen".to_string()),
+            ..Default::default()
+        };
+        self.edit_issue(owner, repo, iid, &opt).await
+    }
+
+    /// Delete an issue (admins and project owners only)
+    pub async fn delete_issue(&self, owner: &str, repo: &str, iid: i64) -> Result<()> {
+        let req = self.request(
+            Method::DELETE,
+            &format!("projects/{}/issues/{}", repo_path(owner, repo), iid),
+        )?;
+        let resp = self.send(req).await;
+        check_success(resp, &format!("delete issue-{} failed", iid)).await
+    }
+
+    /// Move an issue to another project
+    pub async fn move_issue(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+        to_project_id: i64,
+    ) -> Result<Issue> {
+        let req = self
+            .request(
+                Method::POST,
+                &format!("projects/{}/issues/{}/move", repo_path(owner, repo), iid),
+            )?
+            .json(&maplit::hashmap! { "to_project_id" => to_project_id });
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("move issue-{} failed", iid)).await
+    }
+
+    /// List the issues linked to an issue
+    pub async fn list_issue_links(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+    ) -> Result<Vec<LinkedIssue>> {
+        let req = self.request(
+            Method::GET,
+            &format!("projects/{}/issues/{}/links", repo_path(owner, repo), iid),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("list links of issue-{} failed", iid)).await
+    }
+
+    /// Link an issue to another one
+    pub async fn create_issue_link(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+        opt: &CreateIssueLinkOption,
+    ) -> Result<IssueLink> {
+        let req = self
+            .request(
+                Method::POST,
+                &format!("projects/{}/issues/{}/links", repo_path(owner, repo), iid),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("link issue-{} failed", iid)).await
+    }
+
+    /// Remove a link between two issues
+    pub async fn delete_issue_link(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+        issue_link_id: i64,
+    ) -> Result<IssueLink> {
+        let req = self.request(
+            Method::DELETE,
+            &format!(
+                "projects/{}/issues/{}/links/{}",
+                repo_path(owner, repo),
+                iid,
+                issue_link_id
+            ),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(
+            resp,
+            &format!("delete link-{} of issue-{} failed", issue_link_id, iid),
+        )
+        .await
+    }
+
+    /// Get the time tracking stats of an issue
+    pub async fn get_issue_time_stats(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+    ) -> Result<TimeStats> {
+        let req = self.request(
+            Method::GET,
+            &format!(
+                "projects/{}/issues/{}/time_stats",
+                repo_path(owner, repo),
+                iid
+            ),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("get time stats of issue-{} failed", iid)).await
+    }
+
+    /// Set the time estimate of an issue, `duration` is in human format, e.g. `3h30m`
+    pub async fn set_issue_time_estimate(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+        duration: &str,
+    ) -> Result<TimeStats> {
+        let req = self
+            .request(
+                Method::POST,
+                &format!(
+                    "projects/{}/issues/{}/time_estimate",
+                    repo_path(owner, repo),
+                    iid
+                ),
+            )?
+            .query(&[("duration", duration)]);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("set time estimate of issue-{} failed", iid)).await
+    }
+
+    /// Reset the time estimate of an issue
+    pub async fn reset_issue_time_estimate(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+    ) -> Result<TimeStats> {
+        let req = self.request(
+            Method::POST,
+            &format!(
+                "projects/{}/issues/{}/reset_time_estimate",
+                repo_path(owner, repo),
+                iid
+            ),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(
+            resp,
+            &format!("reset time estimate of issue-{} failed", iid),
+        )
+        .await
+    }
+
+    /// Add spent time to an issue, `duration` is in human format, e.g. `3h30m`
+    pub async fn add_issue_spent_time(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+        duration: &str,
+        summary: Option<&str>,
+    ) -> Result<TimeStats> {
+        let req = self
+            .request(
+                Method::POST,
+                &format!(
+                    "projects/{}/issues/{}/add_spent_time",
+                    repo_path(owner, repo),
+                    iid
+                ),
+            )?
+            .query(&[("duration", Some(duration)), ("summary", summary)]);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("add spent time to issue-{} failed", iid)).await
+    }
+
+    /// Reset the spent time of an issue
+    pub async fn reset_issue_spent_time(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+    ) -> Result<TimeStats> {
+        let req = self.request(
+            Method::POST,
+            &format!(
+                "projects/{}/issues/{}/reset_spent_time",
+                repo_path(owner, repo),
+                iid
+            ),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("reset spent time of issue-{} failed", iid)).await
+    }
+
+    /// Subscribe the current user to an issue, succeeds if already subscribed
+    pub async fn subscribe_issue(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+    ) -> Result<()> {
+        let req = self.request(
+            Method::POST,
+            &format!(
+                "projects/{}/issues/{}/subscribe",
+                repo_path(owner, repo),
+                iid
+            ),
+        )?;
+        match self.send(req).await {
+            Err(err) if err.status() == Some(StatusCode::NOT_MODIFIED) => Ok(()),
+            resp => {
+                check_success(resp, &format!("subscribe issue-{} failed", iid)).await
+            }
+        }
+    }
+
+    /// Unsubscribe the current user from an issue, succeeds if not subscribed
+    pub async fn unsubscribe_issue(
+        &self,
+        owner: &str,
+        repo: &str,
+        iid: i64,
+    ) -> Result<()> {
+        let req = self.request(
+            Method::POST,
+            &format!(
+                "projects/{}/issues/{}/unsubscribe",
+                repo_path(owner, repo),
+                iid
+            ),
+        )?;
+        match self.send(req).await {
+            Err(err) if err.status() == Some(StatusCode::NOT_MODIFIED) => Ok(()),
+            resp => {
+                check_success(resp, &format!("unsubscribe issue-{} failed", iid)).await
+            }
+        }
+    }
+
+    // ===============================================
+    // Pipeline related apis
+    // ===============================================
+
+    /// List the pipelines of a repo (first page only)
+    pub async fn list_pipelines(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: Option<ListPipelinesOption>,
+    ) -> Result<Vec<Pipeline>> {
+        let req = self
+            .request(
+                Method::GET,
+                &format!("projects/{}/pipelines", repo_path(owner, repo)),
+            )?
+            .query(&opt);
+        let resp = self.send(req).await;
+        resp_json(resp, "list pipelines failed").await
+    }
+
+    /// Iterate over the pipelines of a repo page by page
+    pub fn list_pipelines_pages(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: Option<ListPipelinesOption>,
+    ) -> Result<Pages<Pipeline>> {
+        self.pages(
+            &format!("projects/{}/pipelines", repo_path(owner, repo)),
+            &opt,
+            "list pipelines failed",
+        )
+    }
+
+    /// Stream the pipelines of a repo
+    pub fn list_pipelines_stream(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: Option<ListPipelinesOption>,
+    ) -> Result<impl Stream<Item = Result<Pipeline>>> {
+        Ok(self.list_pipelines_pages(owner, repo, opt)?.into_stream())
+    }
+
+    /// Get the specified pipeline
+    pub async fn get_pipeline(
+        &self,
+        owner: &str,
+        repo: &str,
+        pipeline_id: i64,
+    ) -> Result<Pipeline> {
+        let req = self.request(
+            Method::GET,
+            &format!(
+                "projects/{}/pipelines/{}",
+                repo_path(owner, repo),
+                pipeline_id
+            ),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("get pipeline-{} failed", pipeline_id)).await
+    }
+
+    /// Get the variables of a pipeline
+    pub async fn get_pipeline_variables(
+        &self,
+        owner: &str,
+        repo: &str,
+        pipeline_id: i64,
+    ) -> Result<Vec<PipelineVariable>> {
+        let req = self.request(
+            Method::GET,
+            &format!(
+                "projects/{}/pipelines/{}/variables",
+                repo_path(owner, repo),
+                pipeline_id
+            ),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(
+            resp,
+            &format!("get variables of pipeline-{} failed", pipeline_id),
+        )
+        .await
+    }
+
+    /// Get the test report summary of a pipeline
+    pub async fn get_pipeline_test_report_summary(
+        &self,
+        owner: &str,
+        repo: &str,
+        pipeline_id: i64,
+    ) -> Result<TestReportSummary> {
+        let req = self.request(
+            Method::GET,
+            &format!(
+                "projects/{}/pipelines/{}/test_report_summary",
+                repo_path(owner, repo),
+                pipeline_id
+            ),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(
+            resp,
+            &format!("get test report summary of pipeline-{} failed", pipeline_id),
+        )
+        .await
+    }
+
+    /// Create a pipeline on a ref
+    pub async fn create_pipeline(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: &CreatePipelineOption,
+    ) -> Result<Pipeline> {
+        let req = self
+            .request(
+                Method::POST,
+                &format!("projects/{}/pipeline", repo_path(owner, repo)),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("create pipeline on {} failed", opt.ref_)).await
+    }
+
+    /// Retry the failed or canceled jobs of a pipeline
+    pub async fn retry_pipeline(
+        &self,
+        owner: &str,
+        repo: &str,
+        pipeline_id: i64,
+    ) -> Result<Pipeline> {
+        let req = self.request(
+            Method::POST,
+            &format!(
+                "projects/{}/pipelines/{}/retry",
+                repo_path(owner, repo),
+                pipeline_id
+            ),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("retry pipeline-{} failed", pipeline_id)).await
+    }
+
+    /// Cancel the running jobs of a pipeline
+    pub async fn cancel_pipeline(
+        &self,
+        owner: &str,
+        repo: &str,
+        pipeline_id: i64,
+    ) -> Result<Pipeline> {
+        let req = self.request(
+            Method::POST,
+            &format!(
+                "projects/{}/pipelines/{}/cancel",
+                repo_path(owner, repo),
+                pipeline_id
+            ),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("cancel pipeline-{} failed", pipeline_id)).await
+    }
+
+    /// Delete a pipeline
+    pub async fn delete_pipeline(
+        &self,
+        owner: &str,
+        repo: &str,
+        pipeline_id: i64,
+    ) -> Result<()> {
+        let req = self.request(
+            Method::DELETE,
+            &format!(
+                "projects/{}/pipelines/{}",
+                repo_path(owner, repo),
+                pipeline_id
+            ),
+        )?;
+        let resp = self.send(req).await;
+        check_success(resp, &format!("delete pipeline-{} failed", pipeline_id)).await
+    }
+
+    /// Trigger a pipeline with a pipeline trigger token
+    pub async fn trigger_pipeline(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: &TriggerPipelineOption,
+    ) -> Result<Pipeline> {
+        let req = self
+            .request(
+                Method::POST,
+                &format!("projects/{}/trigger/pipeline", repo_path(owner, repo)),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("trigger pipeline on {} failed", opt.ref_)).await
+    }
+
+    // ===============================================
+    // Job related apis
+    // ===============================================
+
+    /// List the jobs of a repo (first page only)
+    pub async fn list_jobs(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: Option<ListJobsOption>,
+    ) -> Result<Vec<Job>> {
+        let req = self
+            .request(
+                Method::GET,
+                &format!("projects/{}/jobs", repo_path(owner, repo)),
+            )?
+            .query(&opt);
+        let resp = self.send(req).await;
+        resp_json(resp, "list jobs failed").await
+    }
+
+    /// Iterate over the jobs of a repo page by page
+    pub fn list_jobs_pages(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: Option<ListJobsOption>,
+    ) -> Result<Pages<Job>> {
+        self.pages(
+            &format!("projects/{}/jobs", repo_path(owner, repo)),
+            &opt,
+            "list jobs failed",
+        )
+    }
+
+    /// Stream the jobs of a repo
+    pub fn list_jobs_stream(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: Option<ListJobsOption>,
+    ) -> Result<impl Stream<Item = Result<Job>>> {
+        Ok(self.list_jobs_pages(owner, repo, opt)?.into_stream())
+    }
+
+    /// List the jobs of a pipeline (first page only)
+    pub async fn list_pipeline_jobs(
+        &self,
+        owner: &str,
+        repo: &str,
+        pipeline_id: i64,
+        opt: Option<ListJobsOption>,
+    ) -> Result<Vec<Job>> {
+        let req = self
+            .request(
+                Method::GET,
+                &format!(
+                    "projects/{}/pipelines/{}/jobs",
+                    repo_path(owner, repo),
+                    pipeline_id
+                ),
+            )?
+            .query(&opt);
+        let resp = self.send(req).await;
+        resp_json(
+            resp,
+            &format!("list jobs of pipeline-{} failed", pipeline_id),
+        )
+        .await
+    }
+
+    /// Iterate over the jobs of a pipeline page by page
+    pub fn list_pipeline_jobs_pages(
+        &self,
+        owner: &str,
+        repo: &str,
+        pipeline_id: i64,
+        opt: Option<ListJobsOption>,
+    ) -> Result<Pages<Job>> {
+        self.pages(
+            &format!(
+                "projects/{}/pipelines/{}/jobs",
+                repo_path(owner, repo),
+                pipeline_id
+            ),
+            &opt,
+            &format!("list jobs of pipeline-{} failed", pipeline_id),
+        )
+    }
+
+    /// Stream the jobs of a pipeline
+    pub fn list_pipeline_jobs_stream(
+        &self,
+        owner: &str,
+        repo: &str,
+        pipeline_id: i64,
+        opt: Option<ListJobsOption>,
+    ) -> Result<impl Stream<Item = Result<Job>>> {
+        Ok(self
+            .list_pipeline_jobs_pages(owner, repo, pipeline_id, opt)?
+            .into_stream())
+    }
+
+    /// Get the specified job
+    pub async fn get_job(&self, owner: &str, repo: &str, job_id: i64) -> Result<Job> {
+        let req = self.request(
+            Method::GET,
+            &format!("projects/{}/jobs/{}", repo_path(owner, repo), job_id),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("get job-{} failed", job_id)).await
+    }
+
+    /// Retry a job
+    pub async fn retry_job(&self, owner: &str, repo: &str, job_id: i64) -> Result<Job> {
+        let req = self.request(
+            Method::POST,
+            &format!("projects/{}/jobs/{}/retry", repo_path(owner, repo), job_id),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("retry job-{} failed", job_id)).await
+    }
+
+    /// Cancel a job
+    pub async fn cancel_job(
+        &self,
+        owner: &str,
+        repo: &str,
+        job_id: i64,
+    ) -> Result<Job> {
+        let req = self.request(
+            Method::POST,
+            &format!("projects/{}/jobs/{}/cancel", repo_path(owner, repo), job_id),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("cancel job-{} failed", job_id)).await
+    }
+
+    /// Trigger a manual job
+    pub async fn play_job(
+        &self,
+        owner: &str,
+        repo: &str,
+        job_id: i64,
+        opt: &PlayJobOption,
+    ) -> Result<Job> {
+        let req = self
+            .request(
+                Method::POST,
+                &format!("projects/{}/jobs/{}/play", repo_path(owner, repo), job_id),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("play job-{} failed", job_id)).await
+    }
+
+    /// Erase a job, i.e. remove its artifacts and trace
+    pub async fn erase_job(&self, owner: &str, repo: &str, job_id: i64) -> Result<Job> {
+        let req = self.request(
+            Method::POST,
+            &format!("projects/{}/jobs/{}/erase", repo_path(owner, repo), job_id),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("erase job-{} failed", job_id)).await
+    }
+
+    /// Download the trace (log) of a job as a byte stream
+    pub async fn get_job_trace(
+        &self,
+        owner: &str,
+        repo: &str,
+        job_id: i64,
+    ) -> Result<impl Stream<Item = Result<Bytes>>> {
+        let req = self.request(
+            Method::GET,
+            &format!("projects/{}/jobs/{}/trace", repo_path(owner, repo), job_id),
+        )?;
+        let resp = self.send(req).await;
+        resp_stream(resp, &format!("get trace of job-{} failed", job_id))
+    }
+
+    /// Get the trace of a job starting from the byte `offset`
+    pub async fn get_job_trace_from(
+        &self,
+        owner: &str,
+        repo: &str,
+        job_id: i64,
+        offset: u64,
+    ) -> Result<Bytes> {
+        let req = self
+            .request(
+                Method::GET,
+                &format!("projects/{}/jobs/{}/trace", repo_path(owner, repo), job_id),
+            )?
+            .header(RANGE, format!("bytes={}-", offset));
+        let resp = match self.send(req).await {
+            Err(err) if err.status() == Some(StatusCode::RANGE_NOT_SATISFIABLE) => {
+                return Ok(Bytes::new());
+            }
+            resp => resp.map_err(|e| {
+                e.context(format!("get trace of job-{} failed", job_id))
+            })?,
+        };
+
+        // gitlab may ignore the range and return the whole trace
+        let partial = resp.status() == StatusCode::PARTIAL_CONTENT;
+        let trace = resp.bytes().await?;
+        Ok(match partial {
+            true => trace,
+            false => trace.slice((offset as usize).min(trace.len())..),
+        })
+    }
+
+    /// Follow the trace of a job like `tail -f`, polling every `interval` until the
+    /// job is finished. Each item is the newly appended part of the trace.
+    pub fn tail_job_trace(
+        &self,
+        owner: &str,
+        repo: &str,
+        job_id: i64,
+        interval: Duration,
+    ) -> impl Stream<Item = Result<Bytes>> {
+        let (cli, owner, repo) = (self.clone(), owner.to_string(), repo.to_string());
+        stream::try_unfold((0u64, false), move |(offset, finished)| {
+            let (cli, owner, repo) = (cli.clone(), owner.clone(), repo.clone());
+            async move {
+                if finished {
+                    return Result::Ok(None);
+                }
+                loop {
+                    // check the status before fetching, so that the last fetch gets
+                    // the complete trace
+                    let job = cli.get_job(&owner, &repo, job_id).await?;
+                    let chunk = cli
+                        .get_job_trace_from(&owner, &repo, job_id, offset)
+                        .await?;
+                    if !chunk.is_empty() {
+                        let offset = offset + chunk.len() as u64;
+                        return Ok(Some((chunk, (offset, job.is_finished()))));
+                    }
+                    if job.is_finished() {
+                        return Ok(None);
+                    }
+                    sleep(interval).await;
+                }
+            }
+        })
+    }
+
+    /// Download the artifacts archive of a job as a byte stream
+    pub async fn download_job_artifacts(
+        &self,
+        owner: &str,
+        repo: &str,
+        job_id: i64,
+    ) -> Result<impl Stream<Item = Result<Bytes>>> {
+        let req = self.request(
+            Method::GET,
+            &format!(
+                "projects/{}/jobs/{}/artifacts",
+                repo_path(owner, repo),
+                job_id
+            ),
+        )?;
+        let resp = self.send(req).await;
+        resp_stream(
+            resp,
+            &format!("download artifacts of job-{} failed", job_id),
+        )
+    }
+
+    /// Download a single file from the artifacts archive of a job as a byte stream
+    pub async fn download_job_artifact_file(
+        &self,
+        owner: &str,
+        repo: &str,
+        job_id: i64,
+        artifact_path: &str,
+    ) -> Result<impl Stream<Item = Result<Bytes>>> {
+        let req = self.request(
+            Method::GET,
+            &format!(
+                "projects/{}/jobs/{}/artifacts/{}",
+                repo_path(owner, repo),
+                job_id,
+                encode_file_path(artifact_path)?
+            ),
+        )?;
+        let resp = self.send(req).await;
+        resp_stream(
+            resp,
+            &format!(
+                "download artifact {} of job-{} failed",
+                artifact_path, job_id
+            ),
+        )
+    }
+
+    /// Download the artifacts archive of the latest successful job `job_name` on
+    /// `ref_name` as a byte stream
+    pub async fn download_latest_artifacts(
+        &self,
+        owner: &str,
+        repo: &str,
+        ref_name: &str,
+        job_name: &str,
+    ) -> Result<impl Stream<Item = Result<Bytes>>> {
+        let req = self
+            .request(
+                Method::GET,
+                &format!(
+                    "projects/{}/jobs/artifacts/{}/download",
+                    repo_path(owner, repo),
+                    encode_path(ref_name)
+                ),
+            )?
+            .query(&[("job", job_name)]);
+        let resp = self.send(req).await;
+        resp_stream(
+            resp,
+            &format!("download artifacts of {} on {} failed", job_name, ref_name),
+        )
+    }
+
+    /// Download a single file from the artifacts of the latest successful job
+    /// `job_name` on `ref_name` as a byte stream
+    pub async fn download_latest_artifact_file(
+        &self,
+        owner: &str,
+        repo: &str,
+        ref_name: &str,
+        job_name: &str,
+        artifact_path: &str,
+    ) -> Result<impl Stream<Item = Result<Bytes>>> {
+        let req = self
+            .request(
+                Method::GET,
+                &format!(
+                    "projects/{}/jobs/artifacts/{}/raw/{}",
+                    repo_path(owner, repo),
+                    encode_path(ref_name),
+                    encode_file_path(artifact_path)?
+                ),
+            )?
+            .query(&[("job", job_name)]);
+        let resp = self.send(req).await;
+        resp_stream(
+            resp,
+            &format!(
+                "download artifact {} of {} on {} failed",
+                artifact_path, job_name, ref_name
+            ),
+        )
+    }
+
+    // ===============================================
+    // Branch related apis
+    // ===============================================
+
+    /// List the branches of a repo (first page only)
+    pub async fn list_branches(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: Option<ListBranchesOption>,
+    ) -> Result<Vec<Branch>> {
+        let req = self
+            .request(
+                Method::GET,
+                &format!("projects/{}/repository/branches", repo_path(owner, repo)),
+            )?
+            .query(&opt);
+        let resp = self.send(req).await;
+        resp_json(resp, "list branches failed").await
+    }
+
+    /// Iterate over the branches of a repo page by page
+    pub fn list_branches_pages(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: Option<ListBranchesOption>,
+    ) -> Result<Pages<Branch>> {
+        self.pages(
+            &format!("projects/{}/repository/branches", repo_path(owner, repo)),
+            &opt,
+            "list branches failed",
+        )
+    }
+
+    /// Stream the branches of a repo
+    pub fn list_branches_stream(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: Option<ListBranchesOption>,
+    ) -> Result<impl Stream<Item = Result<Branch>>> {
+        Ok(self.list_branches_pages(owner, repo, opt)?.into_stream())
+    }
+
+    /// Search the branches of a repo by name (first page only)
+    pub async fn search_branches(
+        &self,
+        owner: &str,
+        repo: &str,
+        query: &str,
+    ) -> Result<Vec<Branch>> {
+        let opt = ListBranchesOption {
+            search: Some(query.to_string()),
+            ..Default::default()
+        };
+        self.list_branches(owner, repo, Some(opt)).await
+    }
+
+    /// Get the specified branch
+    pub async fn get_branch(
+        &self,
+        owner: &str,
+        repo: &str,
+        branch: &str,
+    ) -> Result<Branch> {
+        let req = self.request(
+            Method::GET,
+            &format!(
+                "projects/{}/repository/branches/{}",
+                repo_path(owner, repo),
+                encode_path(branch)
+            ),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("get branch {} failed", branch)).await
+    }
+
+    /// Create a branch from `ref_`, which is a branch name or commit sha
+    pub async fn create_branch(
+        &self,
+        owner: &str,
+        repo: &str,
+        branch: &str,
+        ref_: &str,
+    ) -> Result<Branch> {
+        let req = self
+            .request(
+                Method::POST,
+                &format!("projects/{}/repository/branches", repo_path(owner, repo)),
+            )?
+            .json(&maplit::hashmap! { "branch" => branch, "ref" => ref_ });
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("create branch {} failed", branch)).await
+    }
+
+    /// Delete a branch
+    pub async fn delete_branch(
+        &self,
+        owner: &str,
+        repo: &str,
+        branch: &str,
+    ) -> Result<()> {
+        let req = self.request(
+            Method::DELETE,
+            &format!(
+                "projects/{}/repository/branches/{}",
+                repo_path(owner, repo),
+                encode_path(branch)
+            ),
+        )?;
+        let resp = self.send(req).await;
+        check_success(resp, &format!("delete branch {} failed", branch)).await
+    }
+
+    /// Delete all the branches merged into the default branch, protected branches
+    /// are kept. The deletion runs asynchronously.
+    pub async fn delete_merged_branches(&self, owner: &str, repo: &str) -> Result<()> {
+        let req = self.request(
+            Method::DELETE,
+            &format!(
+                "projects/{}/repository/merged_branches",
+                repo_path(owner, repo)
+            ),
+        )?;
+        let resp = self.send(req).await;
+        check_success(resp, "delete merged branches failed").await
+    }
+
+    /// List the protected branches of a repo (first page only)
+    pub async fn list_protected_branches(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> Result<Vec<ProtectedBranch>> {
+        let req = self.request(
+            Method::GET,
+            &format!("projects/{}/protected_branches", repo_path(owner, repo)),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(resp, "list protected branches failed").await
+    }
+
+    /// Iterate over the protected branches of a repo page by page
+    pub fn list_protected_branches_pages(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> Result<Pages<ProtectedBranch>> {
+        self.pages(
+            &format!("projects/{}/protected_branches", repo_path(owner, repo)),
+            &(),
+            "list protected branches failed",
+        )
+    }
+
+    /// Stream the protected branches of a repo
+    pub fn list_protected_branches_stream(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> Result<impl Stream<Item = Result<ProtectedBranch>>> {
+        Ok(self
+            .list_protected_branches_pages(owner, repo)?
+            .into_stream())
+    }
+
+    /// Get the specified protected branch
+    pub async fn get_protected_branch(
+        &self,
+        owner: &str,
+        repo: &str,
+        name: &str,
+    ) -> Result<ProtectedBranch> {
+        let req = self.request(
+            Method::GET,
+            &format!(
+                "projects/{}/protected_branches/{}",
+                repo_path(owner, repo),
+                encode_path(name)
+            ),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("get protected branch {} failed", name)).await
+    }
+
+    /// Protect a branch or the branches matching a wildcard
+    pub async fn protect_branch(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: &ProtectBranchOption,
+    ) -> Result<ProtectedBranch> {
+        let req = self
+            .request(
+                Method::POST,
+                &format!("projects/{}/protected_branches", repo_path(owner, repo)),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("protect branch {} failed", opt.name)).await
+    }
+
+    /// Update the settings of a protected branch
+    pub async fn update_protected_branch(
+        &self,
+        owner: &str,
+        repo: &str,
+        name: &str,
+        opt: &UpdateProtectedBranchOption,
+    ) -> Result<ProtectedBranch> {
+        let req = self
+            .request(
+                Method::PATCH,
+                &format!(
+                    "projects/{}/protected_branches/{}",
+                    repo_path(owner, repo),
+                    encode_path(name)
+                ),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("update protected branch {} failed", name)).await
+    }
+
+    /// Unprotect a branch
+    pub async fn unprotect_branch(
+        &self,
+        owner: &str,
+        repo: &str,
+        name: &str,
+    ) -> Result<()> {
+        let req = self.request(
+            Method::DELETE,
+            &format!(
+                "projects/{}/protected_branches/{}",
+                repo_path(owner, repo),
+                encode_path(name)
+            ),
+        )?;
+        let resp = self.send(req).await;
+        check_success(resp, &format!("unprotect branch {} failed", name)).await
+    }
+
+    // ===============================================
+    // Tag related apis
+    // ===============================================
+
+    /// List the tags of a repo (first page only)
+    pub async fn list_tags(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: Option<ListTagsOption>,
+    ) -> Result<Vec<Tag>> {
+        let req = self
+            .request(
+                Method::GET,
+                &format!("projects/{}/repository/tags", repo_path(owner, repo)),
+            )?
+            .query(&opt);
+        let resp = self.send(req).await;
+        resp_json(resp, "list tags failed").await
+    }
+
+    /// Iterate over the tags of a repo page by page
+    pub fn list_tags_pages(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: Option<ListTagsOption>,
+    ) -> Result<Pages<Tag>> {
+        self.pages(
+            &format!("projects/{}/repository/tags", repo_path(owner, repo)),
+            &opt,
+            "list tags failed",
+        )
+    }
+
+    /// Stream the tags of a repo
+    pub fn list_tags_stream(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: Option<ListTagsOption>,
+    ) -> Result<impl Stream<Item = Result<Tag>>> {
+        Ok(self.list_tags_pages(owner, repo, opt)?.into_stream())
+    }
+
+    /// Get the specified tag
+    pub async fn get_tag(&self, owner: &str, repo: &str, tag: &str) -> Result<Tag> {
+        let req = self.request(
+            Method::GET,
+            &format!(
+                "projects/{}/repository/tags/{}",
+                repo_path(owner, repo),
+                encode_path(tag)
+            ),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("get tag {} failed", tag)).await
+    }
+
+    /// Create a tag
+    pub async fn create_tag(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: &CreateTagOption,
+    ) -> Result<Tag> {
+        let req = self
+            .request(
+                Method::POST,
+                &format!("projects/{}/repository/tags", repo_path(owner, repo)),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("create tag {} failed", opt.tag_name)).await
+    }
+
+    /// Delete a tag
+    pub async fn delete_tag(&self, owner: &str, repo: &str, tag: &str) -> Result<()> {
+        let req = self.request(
+            Method::DELETE,
+            &format!(
+                "projects/{}/repository/tags/{}",
+                repo_path(owner, repo),
+                encode_path(tag)
+            ),
+        )?;
+        let resp = self.send(req).await;
+        check_success(resp, &format!("delete tag {} failed", tag)).await
+    }
+
+    /// List the protected tags of a repo (first page only)
+    pub async fn list_protected_tags(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> Result<Vec<ProtectedTag>> {
+        let req = self.request(
+            Method::GET,
+            &format!("projects/{}/protected_tags", repo_path(owner, repo)),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(resp, "list protected tags failed").await
+    }
+
+    /// Iterate over the protected tags of a repo page by page
+    pub fn list_protected_tags_pages(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> Result<Pages<ProtectedTag>> {
+        self.pages(
+            &format!("projects/{}/protected_tags", repo_path(owner, repo)),
+            &(),
+            "list protected tags failed",
+        )
+    }
+
+    /// Stream the protected tags of a repo
+    pub fn list_protected_tags_stream(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> Result<impl Stream<Item = Result<ProtectedTag>>> {
+        Ok(self.list_protected_tags_pages(owner, repo)?.into_stream())
+    }
+
+    /// Get the specified protected tag
+    pub async fn get_protected_tag(
+        &self,
+        owner: &str,
+        repo: &str,
+        name: &str,
+    ) -> Result<ProtectedTag> {
+        let req = self.request(
+            Method::GET,
+            &format!(
+                "projects/{}/protected_tags/{}",
+                repo_path(owner, repo),
+                encode_path(name)
+            ),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("get protected tag {} failed", name)).await
+    }
+
+    /// Protect a tag or the tags matching a wildcard
+    pub async fn protect_tag(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: &ProtectTagOption,
+    ) -> Result<ProtectedTag> {
+        let req = self
+            .request(
+                Method::POST,
+                &format!("projects/{}/protected_tags", repo_path(owner, repo)),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("protect tag {} failed", opt.name)).await
+    }
+
+    /// Unprotect a tag
+    pub async fn unprotect_tag(
+        &self,
+        owner: &str,
+        repo: &str,
+        name: &str,
+    ) -> Result<()> {
+        let req = self.request(
+            Method::DELETE,
+            &format!(
+                "projects/{}/protected_tags/{}",
+                repo_path(owner, repo),
+                encode_path(name)
+            ),
+        )?;
+        let resp = self.send(req).await;
+        check_success(resp, &format!("unprotect tag {} failed", name)).await
+    }
+
+    // ===============================================
+    // Release related apis
+    // ===============================================
+
+    /// List the releases of a repo (first page only)
+    pub async fn list_releases(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: Option<ListReleasesOption>,
+    ) -> Result<Vec<Release>> {
+        let req = self
+            .request(
+                Method::GET,
+                &format!("projects/{}/releases", repo_path(owner, repo)),
+            )?
+            .query(&opt);
+        let resp = self.send(req).await;
+        resp_json(resp, "list releases failed").await
+    }
+
+    /// Iterate over the releases of a repo page by page
+    pub fn list_releases_pages(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: Option<ListReleasesOption>,
+    ) -> Result<Pages<Release>> {
+        self.pages(
+            &format!("projects/{}/releases", repo_path(owner, repo)),
+            &opt,
+            "list releases failed",
+        )
+    }
+
+    /// Stream the releases of a repo
+    pub fn list_releases_stream(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: Option<ListReleasesOption>,
+    ) -> Result<impl Stream<Item = Result<Release>>> {
+        Ok(self.list_releases_pages(owner, repo, opt)?.into_stream())
+    }
+
+    /// Get the release of a tag
+    pub async fn get_release(
+        &self,
+        owner: &str,
+        repo: &str,
+        tag: &str,
+    ) -> Result<Release> {
+        let req = self.request(
+            Method::GET,
+            &format!(
+                "projects/{}/releases/{}",
+                repo_path(owner, repo),
+                encode_path(tag)
+            ),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("get release {} failed", tag)).await
+    }
+
+    /// Create a release
+    pub async fn create_release(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: &CreateReleaseOption,
+    ) -> Result<Release> {
+        let req = self
+            .request(
+                Method::POST,
+                &format!("projects/{}/releases", repo_path(owner, repo)),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("create release {} failed", opt.tag_name)).await
+    }
+
+    /// Update the release of a tag
+    pub async fn update_release(
+        &self,
+        owner: &str,
+        repo: &str,
+        tag: &str,
+        opt: &UpdateReleaseOption,
+    ) -> Result<Release> {
+        let req = self
+            .request(
+                Method::PUT,
+                &format!(
+                    "projects/{}/releases/{}",
+                    repo_path(owner, repo),
+                    encode_path(tag)
+                ),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("update release {} failed", tag)).await
+    }
+
+    /// Delete the release of a tag, the tag itself is kept
+    pub async fn delete_release(
+        &self,
+        owner: &str,
+        repo: &str,
+        tag: &str,
+    ) -> Result<()> {
+        let req = self.request(
+            Method::DELETE,
+            &format!(
+                "projects/{}/releases/{}",
+                repo_path(owner, repo),
+                encode_path(tag)
+            ),
+        )?;
+        let resp = self.send(req).await;
+        check_success(resp, &format!("delete release {} failed", tag)).await
+    }
+
+    /// Collect the evidence of a release
+    pub async fn collect_release_evidence(
+        &self,
+        owner: &str,
+        repo: &str,
+        tag: &str,
+    ) -> Result<()> {
+        let req = self.request(
+            Method::POST,
+            &format!(
+                "projects/{}/releases/{}/evidence",
+                repo_path(owner, repo),
+                encode_path(tag)
+            ),
+        )?;
+        let resp = self.send(req).await;
+        check_success(resp, &format!("collect evidence of release {} failed", tag))
+            .await
+    }
+
+    /// List the asset links of a release (first page only)
+    pub async fn list_release_links(
+        &self,
+        owner: &str,
+        repo: &str,
+        tag: &str,
+    ) -> Result<Vec<ReleaseLink>> {
+        let req = self.request(
+            Method::GET,
+            &format!(
+                "projects/{}/releases/{}/assets/links",
+                repo_path(owner, repo),
+                encode_path(tag)
+            ),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("list links of release {} failed", tag)).await
+    }
+
+    /// Iterate over the asset links of a release page by page
+    pub fn list_release_links_pages(
+        &self,
+        owner: &str,
+        repo: &str,
+        tag: &str,
+    ) -> Result<Pages<ReleaseLink>> {
+        self.pages(
+            &format!(
+                "projects/{}/releases/{}/assets/links",
+                repo_path(owner, repo),
+                encode_path(tag)
+            ),
+            &(),
+            &format!("list links of release {} failed", tag),
+        )
+    }
+
+    /// Stream the asset links of a release
+    pub fn list_release_links_stream(
+        &self,
+        owner: &str,
+        repo: &str,
+        tag: &str,
+    ) -> Result<impl Stream<Item = Result<ReleaseLink>>> {
+        Ok(self
+            .list_release_links_pages(owner, repo, tag)?
+            .into_stream())
+    }
+
+    /// Get the specified asset link of a release
+    pub async fn get_release_link(
+        &self,
+        owner: &str,
+        repo: &str,
+        tag: &str,
+        link_id: i64,
+    ) -> Result<ReleaseLink> {
+        let req = self.request(
+            Method::GET,
+            &format!(
+                "projects/{}/releases/{}/assets/links/{}",
+                repo_path(owner, repo),
+                encode_path(tag),
+                link_id
+            ),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("get release link-{} failed", link_id)).await
+    }
+
+    /// Add an asset link to a release
+    pub async fn create_release_link(
+        &self,
+        owner: &str,
+        repo: &str,
+        tag: &str,
+        opt: &CreateReleaseLinkOption,
+    ) -> Result<ReleaseLink> {
+        let req = self
+            .request(
+                Method::POST,
+                &format!(
+                    "projects/{}/releases/{}/assets/links",
+                    repo_path(owner, repo),
+                    encode_path(tag)
+                ),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("create link of release {} failed", tag)).await
+    }
+
+    /// Update an asset link of a release
+    pub async fn update_release_link(
+        &self,
+        owner: &str,
+        repo: &str,
+        tag: &str,
+        link_id: i64,
+        opt: &UpdateReleaseLinkOption,
+    ) -> Result<ReleaseLink> {
+        let req = self
+            .request(
+                Method::PUT,
+                &format!(
+                    "projects/{}/releases/{}/assets/links/{}",
+                    repo_path(owner, repo),
+                    encode_path(tag),
+                    link_id
+                ),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("update release link-{} failed", link_id)).await
+    }
+
+    /// Delete an asset link of a release
+    pub async fn delete_release_link(
+        &self,
+        owner: &str,
+        repo: &str,
+        tag: &str,
+        link_id: i64,
+    ) -> Result<()> {
+        let req = self.request(
+            Method::DELETE,
+            &format!(
+                "projects/{}/releases/{}/assets/links/{}",
+                repo_path(owner, repo),
+                encode_path(tag),
+                link_id
+            ),
+        )?;
+        let resp = self.send(req).await;
+        check_success(resp, &format!("delete release link-{} failed", link_id)).await
+    }
+
+    // ===============================================
+    // Repository file related apis
+    // ===============================================
+
+    /// Get a file of a repo at `ref_`, use [`RepoFile::decode_content`] to get its
+    /// content
+    pub async fn get_file(
+        &self,
+        owner: &str,
+        repo: &str,
+        file_path: &str,
+        ref_: &str,
+    ) -> Result<RepoFile> {
+        let req = self
+            .request(
+                Method::GET,
+                &format!(
+                    "projects/{}/repository/files/{}",
+                    repo_path(owner, repo),
+                    encode_path(file_path)
+                ),
+            )?
+            .query(&[("ref", ref_)]);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("get file {} failed", file_path)).await
+    }
+
+    /// Get the metadata of a file of a repo at `ref_` without downloading it
+    pub async fn get_file_meta(
+        &self,
+        owner: &str,
+        repo: &str,
+        file_path: &str,
+        ref_: &str,
+    ) -> Result<FileMeta> {
+        let req = self
+            .request(
+                Method::HEAD,
+                &format!(
+                    "projects/{}/repository/files/{}",
+                    repo_path(owner, repo),
+                    encode_path(file_path)
+                ),
+            )?
+            .query(&[("ref", ref_)]);
+        let resp = self.send(req).await.map_err(|e| {
+            e.context(format!("get metadata of file {} failed", file_path))
+        })?;
+        FileMeta::from_headers(resp.headers())
+    }
+
+    /// Download the raw content of a file as a byte stream, `ref_` defaults to the
+    /// default branch
+    pub async fn get_raw_file(
+        &self,
+        owner: &str,
+        repo: &str,
+        file_path: &str,
+        ref_: Option<&str>,
+    ) -> Result<impl Stream<Item = Result<Bytes>>> {
+        let req = self
+            .request(
+                Method::GET,
+                &format!(
+                    "projects/{}/repository/files/{}/raw",
+                    repo_path(owner, repo),
+                    encode_path(file_path)
+                ),
+            )?
+            .query(&[("ref", ref_)]);
+        let resp = self.send(req).await;
+        resp_stream(resp, &format!("get raw file {} failed", file_path))
+    }
+
+    /// Blame a file, optionally limited to a range of lines
+    pub async fn blame_file(
+        &self,
+        owner: &str,
+        repo: &str,
+        file_path: &str,
+        opt: &BlameFileOption,
+    ) -> Result<Vec<BlameRange>> {
+        let req = self
+            .request(
+                Method::GET,
+                &format!(
+                    "projects/{}/repository/files/{}/blame",
+                    repo_path(owner, repo),
+                    encode_path(file_path)
+                ),
+            )?
+            .query(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("blame file {} failed", file_path)).await
+    }
+
+    /// Create a file in a repo
+    pub async fn create_file(
+        &self,
+        owner: &str,
+        repo: &str,
+        file_path: &str,
+        opt: &CreateFileOption,
+    ) -> Result<FileCommit> {
+        let req = self
+            .request(
+                Method::POST,
+                &format!(
+                    "projects/{}/repository/files/{}",
+                    repo_path(owner, repo),
+                    encode_path(file_path)
+                ),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("create file {} failed", file_path)).await
+    }
+
+    /// Update a file in a repo. With `last_commit_id` set, the update fails with a
+    /// conflict if the file was changed in between, see [`Error::is_conflict`].
+    pub async fn update_file(
+        &self,
+        owner: &str,
+        repo: &str,
+        file_path: &str,
+        opt: &UpdateFileOption,
+    ) -> Result<FileCommit> {
+        let req = self
+            .request(
+                Method::PUT,
+                &format!(
+                    "projects/{}/repository/files/{}",
+                    repo_path(owner, repo),
+                    encode_path(file_path)
+                ),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("update file {} failed", file_path)).await
+    }
+
+    /// Delete a file in a repo
+    pub async fn delete_file(
+        &self,
+        owner: &str,
+        repo: &str,
+        file_path: &str,
+        opt: &DeleteFileOption,
+    ) -> Result<()> {
+        let req = self
+            .request(
+                Method::DELETE,
+                &format!(
+                    "projects/{}/repository/files/{}",
+                    repo_path(owner, repo),
+                    encode_path(file_path)
+                ),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        check_success(resp, &format!("delete file {} failed", file_path)).await
+    }
+
+    // ===============================================
+    // Repository tree related apis
+    // ===============================================
+
+    /// List the files and directories of a repo (first page only)
+    pub async fn list_tree(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: Option<ListTreeOption>,
+    ) -> Result<Vec<TreeNode>> {
+        let req = self
+            .request(
+                Method::GET,
+                &format!("projects/{}/repository/tree", repo_path(owner, repo)),
+            )?
+            .query(&opt);
+        let resp = self.send(req).await;
+        resp_json(resp, "list tree failed").await
+    }
+
+    /// Iterate over the files and directories of a repo page by page
+    pub fn list_tree_pages(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: Option<ListTreeOption>,
+    ) -> Result<Pages<TreeNode>> {
+        self.pages(
+            &format!("projects/{}/repository/tree", repo_path(owner, repo)),
+            &opt,
+            "list tree failed",
+        )
+    }
+
+    /// Stream the files and directories of a repo, use `recursive` to walk the
+    /// whole tree
+    pub fn list_tree_stream(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: Option<ListTreeOption>,
+    ) -> Result<impl Stream<Item = Result<TreeNode>>> {
+        Ok(self.list_tree_pages(owner, repo, opt)?.into_stream())
+    }
+
+    /// Compare two refs, returning the commits and diffs between them
+    pub async fn compare(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: &CompareOption,
+    ) -> Result<Compare> {
+        let req = self
+            .request(
+                Method::GET,
+                &format!("projects/{}/repository/compare", repo_path(owner, repo)),
+            )?
+            .query(opt);
+        let resp = self.send(req).await;
+        resp_json(
+            resp,
+            &format!("compare {} with {} failed", opt.from, opt.to),
+        )
+        .await
+    }
+
+    /// Download an archive of a repo as a byte stream, `format` is e.g. `tar.gz`,
+    /// `tar.bz2`, `tar` or `zip`
+    pub async fn download_archive(
+        &self,
+        owner: &str,
+        repo: &str,
+        format: &str,
+        opt: Option<ArchiveOption>,
+    ) -> Result<impl Stream<Item = Result<Bytes>>> {
+        let req = self
+            .request(
+                Method::GET,
+                &format!(
+                    "projects/{}/repository/archive.{}",
+                    repo_path(owner, repo),
+                    format
+                ),
+            )?
+            .query(&opt);
+        let resp = self.send(req).await;
+        resp_stream(resp, "download archive failed")
+    }
+
+    /// Get the common ancestor of two or more refs
+    pub async fn merge_base(
+        &self,
+        owner: &str,
+        repo: &str,
+        refs: &[&str],
+    ) -> Result<Commit> {
+        let query: Vec<_> = refs.iter().map(|r| ("refs[]", *r)).collect();
+        let req = self
+            .request(
+                Method::GET,
+                &format!("projects/{}/repository/merge_base", repo_path(owner, repo)),
+            )?
+            .query(&query);
+        let resp = self.send(req).await;
+        resp_json(
+            resp,
+            &format!("get merge base of {} failed", refs.join(", ")),
+        )
+        .await
+    }
+
+    /// List the contributors of a repo with their stats (first page only)
+    pub async fn list_contributors(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: Option<ListContributorsOption>,
+    ) -> Result<Vec<Contributor>> {
+        let req = self
+            .request(
+                Method::GET,
+                &format!(
+                    "projects/{}/repository/contributors",
+                    repo_path(owner, repo)
+                ),
+            )?
+            .query(&opt);
+        let resp = self.send(req).await;
+        resp_json(resp, "list contributors failed").await
+    }
+
+    /// Iterate over the contributors of a repo page by page
+    pub fn list_contributors_pages(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: Option<ListContributorsOption>,
+    ) -> Result<Pages<Contributor>> {
+        self.pages(
+            &format!(
+                "projects/{}/repository/contributors",
+                repo_path(owner, repo)
+            ),
+            &opt,
+            "list contributors failed",
+        )
+    }
+
+    /// Stream the contributors of a repo
+    pub fn list_contributors_stream(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: Option<ListContributorsOption>,
+    ) -> Result<impl Stream<Item = Result<Contributor>>> {
+        Ok(self
+            .list_contributors_pages(owner, repo, opt)?
+            .into_stream())
+    }
+
+    /// Generate the changelog notes of a version without committing them
+    pub async fn generate_changelog(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: &GenerateChangelogOption,
+    ) -> Result<ChangelogNotes> {
+        let req = self
+            .request(
+                Method::GET,
+                &format!("projects/{}/repository/changelog", repo_path(owner, repo)),
+            )?
+            .query(opt);
+        let resp = self.send(req).await;
+        resp_json(
+            resp,
+            &format!("generate changelog of {} failed", opt.version),
+        )
+        .await
+    }
+
+    /// Generate the changelog notes of a version and commit them to the changelog
+    /// file
+    pub async fn commit_changelog(
+        &self,
+        owner: &str,
+        repo: &str,
+        opt: &CommitChangelogOption,
+    ) -> Result<()> {
+        let req = self
+            .request(
+                Method::POST,
+                &format!("projects/{}/repository/changelog", repo_path(owner, repo)),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        check_success(
+            resp,
+            &format!("commit changelog of {} failed", opt.changelog.version),
+        )
+        .await
+    }
+
+    // ===============================================
+    // Group and system hook related apis
+    // ===============================================
+
+    /// List the webhooks of a group (first page only)
+    pub async fn list_group_hooks(&self, group: &str) -> Result<Vec<GroupHook>> {
+        let req =
+            self.request(Method::GET, &format!("groups/{}/hooks", group_path(group)))?;
+        let resp = self.send(req).await;
+        resp_json(resp, "list group hooks failed").await
+    }
+
+    /// Iterate over the webhooks of a group page by page
+    pub fn list_group_hooks_pages(&self, group: &str) -> Result<Pages<GroupHook>> {
+        self.pages(
+            &format!("groups/{}/hooks", group_path(group)),
+            &(),
+            "list group hooks failed",
+        )
+    }
+
+    /// Stream the webhooks of a group
+    pub fn list_group_hooks_stream(
+        &self,
+        group: &str,
+    ) -> Result<impl Stream<Item = Result<GroupHook>>> {
+        Ok(self.list_group_hooks_pages(group)?.into_stream())
+    }
+
+    /// Get the specified webhook of a group
+    pub async fn get_group_hook(&self, group: &str, id: i64) -> Result<GroupHook> {
+        let req = self.request(
+            Method::GET,
+            &format!("groups/{}/hooks/{}", group_path(group), id),
+        )?;
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("get group hook-{} failed", id)).await
+    }
+
+    /// Create a webhook of a group
+    pub async fn create_group_hook(
+        &self,
+        group: &str,
+        opt: &CreateGroupHookOption,
+    ) -> Result<GroupHook> {
+        let req = self
+            .request(Method::POST, &format!("groups/{}/hooks", group_path(group)))?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, "create group hook failed").await
+    }
+
+    /// Edit a webhook of a group, only the settings set in `opt` are updated and the
+    /// fields left to `None` are unchanged
+    pub async fn edit_group_hook(
+        &self,
+        group: &str,
+        id: i64,
+        opt: &EditGroupHookOption,
+    ) -> Result<GroupHook> {
+        let req = self
+            .request(
+                Method::PUT,
+                &format!("groups/{}/hooks/{}", group_path(group), id),
+            )?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("edit group hook-{} failed", id)).await
+    }
+
+    /// Delete a webhook of a group
+    pub async fn delete_group_hook(&self, group: &str, id: i64) -> Result<()> {
+        let req = self.request(
+            Method::DELETE,
+            &format!("groups/{}/hooks/{}", group_path(group), id),
+        )?;
+        let resp = self.send(req).await;
+        check_success(resp, &format!("delete group hook-{} failed", id)).await
+    }
+
+    /// Send a test event to a webhook of a group, `trigger` is the event flag to
+    /// test, e.g. `push_events` or `subgroup_events`
+    pub async fn test_group_hook(
+        &self,
+        group: &str,
+        id: i64,
+        trigger: &str,
+    ) -> Result<()> {
+        let req = self.request(
+            Method::POST,
+            &format!("groups/{}/hooks/{}/test/{}", group_path(group), id, trigger),
+        )?;
+        let resp = self.send(req).await;
+        check_success(
+            resp,
+            &format!("test {} of group hook-{} failed", trigger, id),
+        )
+        .await
+    }
+
+    /// Set a custom header sent by a webhook of a group
+    pub async fn set_group_hook_custom_header(
+        &self,
+        group: &str,
+        id: i64,
+        key: &str,
+        value: &str,
+    ) -> Result<()> {
+        let req = self
+            .request(
+                Method::PUT,
+                &format!(
+                    "groups/{}/hooks/{}/custom_headers/{}",
+                    group_path(group),
+                    id,
+                    encode_path(key)
+                ),
+            )?
+            .json(&maplit::hashmap! { "value" => value });
+        let resp = self.send(req).await;
+        check_success(
+            resp,
+            &format!("set header {} of group hook-{} failed", key, id),
+        )
+        .await
+    }
+
+    /// Delete a custom header of a webhook of a group
+    pub async fn delete_group_hook_custom_header(
+        &self,
+        group: &str,
+        id: i64,
+        key: &str,
+    ) -> Result<()> {
+        let req = self.request(
+            Method::DELETE,
+            &format!(
+                "groups/{}/hooks/{}/custom_headers/{}",
+                group_path(group),
+                id,
+                encode_path(key)
+            ),
+        )?;
+        let resp = self.send(req).await;
+        check_success(
+            resp,
+            &format!("delete header {} of group hook-{} failed", key, id),
+        )
+        .await
+    }
+
+    /// Set a variable substituted in the url of a webhook of a group
+    pub async fn set_group_hook_url_variable(
+        &self,
+        group: &str,
+        id: i64,
+        key: &str,
+        value: &str,
+    ) -> Result<()> {
+        let req = self
+            .request(
+                Method::PUT,
+                &format!(
+                    "groups/{}/hooks/{}/url_variables/{}",
+                    group_path(group),
+                    id,
+                    encode_path(key)
+                ),
+            )?
+            .json(&maplit::hashmap! { "value" => value });
+        let resp = self.send(req).await;
+        check_success(
+            resp,
+            &format!("set url variable {} of group hook-{} failed", key, id),
+        )
+        .await
+    }
+
+    /// Delete a url variable of a webhook of a group
+    pub async fn delete_group_hook_url_variable(
+        &self,
+        group: &str,
+        id: i64,
+        key: &str,
+    ) -> Result<()> {
+        let req = self.request(
+            Method::DELETE,
+            &format!(
+                "groups/{}/hooks/{}/url_variables/{}",
+                group_path(group),
+                id,
+                encode_path(key)
+            ),
+        )?;
+        let resp = self.send(req).await;
+        check_success(
+            resp,
+            &format!("delete url variable {} of group hook-{} failed", key, id),
+        )
+        .await
+    }
+
+    /// List the system hooks (admins only, first page only)
+    pub async fn list_system_hooks(&self) -> Result<Vec<SystemHook>> {
+        let req = self.request(Method::GET, "hooks")?;
+        let resp = self.send(req).await;
+        resp_json(resp, "list system hooks failed").await
+    }
+
+    /// Iterate over the system hooks page by page
+    pub fn list_system_hooks_pages(&self) -> Result<Pages<SystemHook>> {
+        self.pages("hooks", &(), "list system hooks failed")
+    }
+
+    /// Stream the system hooks
+    pub fn list_system_hooks_stream(
+        &self,
+    ) -> Result<impl Stream<Item = Result<SystemHook>>> {
+        Ok(self.list_system_hooks_pages()?.into_stream())
+    }
+
+    /// Get the specified system hook
+    pub async fn get_system_hook(&self, id: i64) -> Result<SystemHook> {
+        let req = self.request(Method::GET, &format!("hooks/{}", id))?;
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("get system hook-{} failed", id)).await
+    }
+
+    /// Create a system hook
+    pub async fn create_system_hook(
+        &self,
+        opt: &CreateSystemHookOption,
+    ) -> Result<SystemHook> {
+        let req = self.request(Method::POST, "hooks")?.json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, "create system hook failed").await
+    }
+
+    /// Edit a system hook, only the settings set in `opt` are updated and the fields
+    /// left to `None` are unchanged
+    pub async fn edit_system_hook(
+        &self,
+        id: i64,
+        opt: &CreateSystemHookOption,
+    ) -> Result<SystemHook> {
+        let req = self
+            .request(Method::PUT, &format!("hooks/{}", id))?
+            .json(opt);
+        let resp = self.send(req).await;
+        resp_json(resp, &format!("edit system hook-{} failed", id)).await
+    }
+
+    /// Delete a system hook
+    pub async fn delete_system_hook(&self, id: i64) -> Result<()> {
+        let req = self.request(Method::DELETE, &format!("hooks/{}", id))?;
+        let resp = self.send(req).await;
+        check_success(resp, &format!("delete system hook-{} failed", id)).await
+    }
+
+    /// Send a test event to a system hook
+    pub async fn test_system_hook(&self, id: i64) -> Result<()> {
+        let req = self.request(Method::POST, &format!("hooks/{}", id))?;
+        let resp = self.send(req).await;
+        check_success(resp, &format!("test system hook-{} failed", id)).await
+    }
+
+    /// Set a variable substituted in the url of a system hook
+    pub async fn set_system_hook_url_variable(
+        &self,
+        id: i64,
+        key: &str,
+        value: &str,
+    ) -> Result<()> {
+        let req = self
+            .request(
+                Method::PUT,
+                &format!("hooks/{}/url_variables/{}", id, encode_path(key)),
+            )?
+            .json(&maplit::hashmap! { "value" => value });
+        let resp = self.send(req).await;
+        check_success(
+            resp,
+            &format!("set url variable {} of system hook-{} failed", key, id),
+        )
+        .await
+    }
+
+    /// Delete a url variable of a system hook
+    pub async fn delete_system_hook_url_variable(
+        &self,
+        id: i64,
+        key: &str,
+    ) -> Result<()> {
+        let req = self.request(
+            Method::DELETE,
+            &format!("hooks/{}/url_variables/{}", id, encode_path(key)),
+        )?;
+        let resp = self.send(req).await;
+        check_success(
+            resp,
+            &format!("delete url variable {} of system hook-{} failed", key, id),
+        )
+        .await
+    }
+}
+
+pub async fn resp_json<T>(resp: Result<Response>, err_mes: &str) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    Ok(resp.map_err(|e| e.context(err_mes))?.json::<T>().await?)
+}
+
+/// Turn the body of the response into a byte stream, without buffering it
+pub fn resp_stream(
+    resp: Result<Response>,
+    err_mes: &str,
+) -> Result<impl Stream<Item = Result<Bytes>>> {
+    let resp = resp.map_err(|e| e.context(err_mes))?;
+    Ok(resp.bytes_stream().map_err(Error::from))
+}
+
+pub async fn check_success(resp: Result<Response>, err_mes: &str) -> Result<()> {
+    resp.map_err(|e| e.context(err_mes))?;
+    Ok(())
+}
+
+pub fn repo_path(owner: &str, repo: &str) -> String {
+    format!("{}%2F{}", owner, repo)
+}
+
+pub fn group_path(group: &str) -> String {
+    group.replace('/', "%2F")
+}
+
+/// Percent-encode a value used as a single path segment, e.g. a ref name or file path
+pub fn encode_path(path: &str) -> String {
+    path.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Percent-encode a file path spanning several path segments, e.g. an artifact path,
+/// keeping its `/` separators. `.` and `..` segments are rejected, as they would
+/// escape the endpoint once the url is resolved.
+fn encode_file_path(path: &str) -> Result<String> {
+    path.trim_start_matches('/')
+        .split('/')
+        .map(|seg| match seg {
+            "." | ".." => Err(Error::Other(format!("invalid file path {}", path))),
+            _ => Ok(encode_path(seg)),
+        })
+        .collect::<Result<Vec<_>>>()
+        .map(|segs| segs.join("/"))
+}
+
+pub async fn debug_resp(resp: reqwest::Response, start: usize) {
+    println!("{:#?}", &resp.text().await.unwrap()[start..start + 50]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_file_path_keeps_separators() {
+        assert_eq!(
+            encode_file_path("/out/report v1.xml").unwrap(),
+            "out/report%20v1.xml"
+        );
+        assert_eq!(encode_file_path("a?b#c%d/e").unwrap(), "a%3Fb%23c%25d/e");
+
+        let base = Url::parse("https://gitlab.example.com/api/v4/").unwrap();
+        let url = base
+            .join(&format!(
+                "projects/1/jobs/2/artifacts/{}",
+                encode_file_path("dist/a?b#c").unwrap()
+            ))
+            .unwrap();
+        assert_eq!(
+            url.path(),
+            "/api/v4/projects/1/jobs/2/artifacts/dist/a%3Fb%23c"
+        );
+        assert_eq!((url.query(), url.fragment()), (None, None));
+    }
+
+    #[test]
+    fn encode_file_path_rejects_dot_segments() {
+        assert!(encode_file_path("../../../../user").is_err());
+        assert!(encode_file_path("dist/../secret").is_err());
+        assert!(encode_file_path("./dist").is_err());
+        assert!(encode_file_path("dist/..hidden").is_ok());
+    }
 }
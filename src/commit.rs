@@ -1,6 +1,8 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::user::BasicUser;
+
 #[derive(Debug, Default, Deserialize, Serialize)]
 pub struct ListCommitsOption {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -43,9 +45,275 @@ pub struct Commit {
     pub committed_date: DateTime<Utc>,
     pub created_at: DateTime<Utc>,
     pub message: String,
+    #[serde(default)]
     pub parent_ids: Vec<String>,
     pub web_url: String,
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stats: Option<Stat>,
 }
+
+/// The diff of a file
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Diff {
+    pub old_path: String,
+    pub new_path: String,
+    pub a_mode: Option<String>,
+    pub b_mode: Option<String>,
+    pub diff: String,
+    pub new_file: bool,
+    pub renamed_file: bool,
+    pub deleted_file: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CommitActionKind {
+    Create,
+    Delete,
+    Move,
+    Update,
+    Chmod,
+}
+
+/// A single file change of a commit
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CommitAction {
+    pub action: CommitActionKind,
+    pub file_path: String,
+    /// Original path of a moved file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    /// `text` or `base64`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+    /// Fail with a conflict if the file was changed since this commit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_commit_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execute_filemode: Option<bool>,
+}
+
+impl CommitAction {
+    fn new(action: CommitActionKind, file_path: &str) -> Self {
+        Self {
+            action,
+            file_path: file_path.to_string(),
+            previous_path: None,
+            content: None,
+            encoding: None,
+            last_commit_id: None,
+            execute_filemode: None,
+        }
+    }
+
+    pub fn create(file_path: &str, content: &str) -> Self {
+        Self {
+            content: Some(content.to_string()),
+            ..Self::new(CommitActionKind::Create, file_path)
+        }
+    }
+
+    pub fn update(file_path: &str, content: &str) -> Self {
+        Self {
+            content: Some(content.to_string()),
+            ..Self::new(CommitActionKind::Update, file_path)
+        }
+    }
+
+    pub fn delete(file_path: &str) -> Self {
+        Self::new(CommitActionKind::Delete, file_path)
+    }
+
+    /// Move `previous_path` to `file_path`, keeping its content
+    pub fn move_file(previous_path: &str, file_path: &str) -> Self {
+        Self {
+            previous_path: Some(previous_path.to_string()),
+            ..Self::new(CommitActionKind::Move, file_path)
+        }
+    }
+
+    pub fn chmod(file_path: &str, execute_filemode: bool) -> Self {
+        Self {
+            execute_filemode: Some(execute_filemode),
+            ..Self::new(CommitActionKind::Chmod, file_path)
+        }
+    }
+
+    /// Set binary content, sent base64 encoded
+    pub fn binary_content(mut self, content: &[u8]) -> Self {
+        self.content = Some(base64::encode(content));
+        self.encoding = Some("base64".to_string());
+        self
+    }
+
+    pub fn last_commit_id(mut self, last_commit_id: &str) -> Self {
+        self.last_commit_id = Some(last_commit_id.to_string());
+        self
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct CreateCommitOption {
+    pub branch: String,
+    pub commit_message: String,
+    /// Create `branch` from this branch if it doesn't exist
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_branch: Option<String>,
+    /// Create `branch` from this sha if it doesn't exist
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_sha: Option<String>,
+    /// Id or url-encoded path of the project to start from, defaults to this one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_project: Option<String>,
+    pub actions: Vec<CommitAction>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author_email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author_name: Option<String>,
+    /// Include the commit stats, defaults to true
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<bool>,
+    /// Overwrite `branch` with a new commit based on `start_branch` or `start_sha`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub force: Option<bool>,
+}
+
+impl CreateCommitOption {
+    pub fn new(branch: &str, commit_message: &str, actions: Vec<CommitAction>) -> Self {
+        Self {
+            branch: branch.to_string(),
+            commit_message: commit_message.to_string(),
+            actions,
+            ..Default::default()
+        }
+    }
+}
+
+/// A comment on a commit, optionally on a line of its diff
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CommitComment {
+    pub note: String,
+    pub author: BasicUser,
+    pub path: Option<String>,
+    pub line: Option<i64>,
+    /// `new` or `old`
+    pub line_type: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct CreateCommitCommentOption {
+    pub note: String,
+    /// File path, relative to the repo
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<i64>,
+    /// `new` or `old`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_type: Option<String>,
+}
+
+impl CreateCommitCommentOption {
+    pub fn new(note: &str) -> Self {
+        Self {
+            note: note.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+/// A branch or tag containing a commit
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CommitRef {
+    /// `branch` or `tag`
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub name: String,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ListCommitRefsOption {
+    /// `branch`, `tag` or `all`
+    #[serde(rename = "type")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub type_: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct CherryPickCommitOption {
+    /// Target branch
+    pub branch: String,
+    /// Only check whether the cherry-pick would succeed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dry_run: Option<bool>,
+    /// Custom commit message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl CherryPickCommitOption {
+    pub fn new(branch: &str) -> Self {
+        Self {
+            branch: branch.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct RevertCommitOption {
+    /// Target branch
+    pub branch: String,
+    /// Only check whether the revert would succeed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dry_run: Option<bool>,
+}
+
+impl RevertCommitOption {
+    pub fn new(branch: &str) -> Self {
+        Self {
+            branch: branch.to_string(),
+            dry_run: None,
+        }
+    }
+}
+
+/// The result of a cherry-pick or a revert, gitlab answers `{"dry_run": "success"}`
+/// instead of the new commit when `dry_run` is set
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum CommitResult {
+    Commit(Box<Commit>),
+    DryRun { dry_run: String },
+}
+
+impl CommitResult {
+    /// The created commit, `None` for a dry run
+    pub fn commit(self) -> Option<Commit> {
+        match self {
+            CommitResult::Commit(commit) => Some(*commit),
+            CommitResult::DryRun { .. } => None,
+        }
+    }
+}
+
+/// Signature of a commit, the key fields depend on `signature_type`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CommitSignature {
+    /// `PGP`, `X509` or `SSH`
+    pub signature_type: String,
+    /// e.g. `verified`, `unverified` or `unknown_key`
+    pub verification_status: String,
+    pub gpg_key_id: Option<i64>,
+    pub gpg_key_primary_keyid: Option<String>,
+    pub gpg_key_user_name: Option<String>,
+    pub gpg_key_user_email: Option<String>,
+    pub gpg_key_subkey_id: Option<i64>,
+    pub commit_source: Option<String>,
+    pub x509_certificate: Option<serde_json::Value>,
+    pub key: Option<serde_json::Value>,
+}
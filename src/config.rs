@@ -1,9 +1,13 @@
 use url::Url;
 
-use crate::auth::Auth;
+use crate::{auth::Auth, ratelimit::RateLimitPolicy, retry::RetryPolicy};
 
 #[derive(Debug, Clone)]
 pub struct Config {
     pub base_url: Url,
     pub token: Auth,
+    pub retry: RetryPolicy,
+    pub rate_limit: RateLimitPolicy,
+    /// Default items per page of the paginated apis, gitlab defaults to 20
+    pub per_page: Option<u32>,
 }
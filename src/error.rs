@@ -1,6 +1,9 @@
-use std::sync::PoisonError;
+use std::{collections::BTreeMap, fmt, sync::PoisonError};
 
+use http::{Method, StatusCode};
+use serde_json::Value;
 use thiserror::Error as ThisError;
+use url::Url;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -13,7 +16,7 @@ pub enum Error {
     Unauthorized(String),
 
     #[error("gitlab error: {0}")]
-    GitlabError(String),
+    GitlabError(Box<ApiError>),
 
     #[error("{0}")]
     ReqwestError(#[from] reqwest::Error),
@@ -24,9 +27,15 @@ pub enum Error {
     #[error("{0}")]
     AnyhowError(#[from] anyhow::Error),
 
+    #[error("{0}")]
+    UrlEncodeError(#[from] serde_urlencoded::ser::Error),
+
     #[error("{0}")]
     SerdeJsonError(#[from] serde_json::Error),
 
+    #[error("{0}")]
+    Base64DecodeError(#[from] base64::DecodeError),
+
     #[error("{0}")]
     Other(String),
 }
@@ -36,3 +45,166 @@ impl<T> From<PoisonError<T>> for Error {
         Self::Other(err.to_string())
     }
 }
+
+impl Error {
+    /// The detail of the error response returned by gitlab
+    pub fn api_error(&self) -> Option<&ApiError> {
+        match self {
+            Error::GitlabError(err) => Some(err),
+            _ => None,
+        }
+    }
+
+    /// The http status code of the error response returned by gitlab
+    pub fn status(&self) -> Option<StatusCode> {
+        self.api_error().map(|err| err.status)
+    }
+
+    /// Attach a context message to the error returned by gitlab, wrapping the context
+    /// attached before
+    pub fn context(mut self, context: impl Into<String>) -> Self {
+        if let Error::GitlabError(err) = &mut self {
+            let context = context.into();
+            err.context = Some(match err.context.take() {
+                Some(inner) => format!("{}: {}", context, inner),
+                None => context,
+            });
+        }
+        self
+    }
+
+    pub fn is_unauthorized(&self) -> bool {
+        matches!(self, Error::Unauthorized(_))
+            || self.status() == Some(StatusCode::UNAUTHORIZED)
+    }
+
+    pub fn is_forbidden(&self) -> bool {
+        self.status() == Some(StatusCode::FORBIDDEN)
+    }
+
+    pub fn is_not_found(&self) -> bool {
+        self.status() == Some(StatusCode::NOT_FOUND)
+    }
+
+    pub fn is_conflict(&self) -> bool {
+        self.status() == Some(StatusCode::CONFLICT)
+    }
+
+    pub fn is_rate_limited(&self) -> bool {
+        self.status() == Some(StatusCode::TOO_MANY_REQUESTS)
+    }
+
+    pub fn is_server_error(&self) -> bool {
+        self.status().is_some_and(|status| status.is_server_error())
+    }
+}
+
+/// An error response returned by gitlab
+#[derive(Debug, Clone)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub method: Method,
+    pub url: Url,
+    /// The `message` of the response body
+    pub message: Option<String>,
+    /// The `error` (and `error_description`) of the response body, e.g. oauth errors
+    pub error: Option<String>,
+    /// Field-level validation errors, e.g. `{"message": {"name": ["has already been taken"]}}`
+    pub field_errors: BTreeMap<String, Vec<String>>,
+    /// The raw response body
+    pub body: String,
+    /// What the client was doing, e.g. `get repo failed`
+    pub context: Option<String>,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, method: Method, url: Url, body: String) -> Self {
+        let mut err = Self {
+            status,
+            method,
+            url,
+            message: None,
+            error: None,
+            field_errors: BTreeMap::new(),
+            body,
+            context: None,
+        };
+
+        let value = match serde_json::from_str::<Value>(&err.body) {
+            Ok(Value::Object(value)) => value,
+            _ => return err,
+        };
+        match value.get("message") {
+            Some(Value::String(message)) => err.message = Some(message.clone()),
+            Some(Value::Array(messages)) => {
+                err.message = Some(
+                    messages
+                        .iter()
+                        .map(value_to_string)
+                        .collect::<Vec<_>>()
+                        .join("; "),
+                )
+            }
+            Some(Value::Object(fields)) => {
+                for (field, errors) in fields {
+                    let errors = match errors {
+                        Value::Array(errors) => {
+                            errors.iter().map(value_to_string).collect()
+                        }
+                        other => vec![value_to_string(other)],
+                    };
+                    err.field_errors.insert(field.clone(), errors);
+                }
+            }
+            _ => {}
+        }
+        err.error = match (value.get("error"), value.get("error_description")) {
+            (Some(error), Some(desc)) => Some(format!(
+                "{}: {}",
+                value_to_string(error),
+                value_to_string(desc)
+            )),
+            (Some(error), None) => Some(value_to_string(error)),
+            _ => None,
+        };
+        err
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(context) = &self.context {
+            write!(f, "{}: ", context)?;
+        }
+        write!(f, "[{}] {} {}", self.status, self.method, self.url)?;
+        if let Some(message) = &self.message {
+            write!(f, ": {}", message)?;
+        }
+        if let Some(error) = &self.error {
+            write!(f, ": {}", error)?;
+        }
+        for (field, errors) in &self.field_errors {
+            write!(f, ": {} {}", field, errors.join(", "))?;
+        }
+        if self.message.is_none()
+            && self.error.is_none()
+            && self.field_errors.is_empty()
+        {
+            write!(f, ": {}", self.body)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<ApiError> for Error {
+    fn from(err: ApiError) -> Self {
+        Self::GitlabError(Box::new(err))
+    }
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
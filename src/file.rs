@@ -0,0 +1,245 @@
+use chrono::{DateTime, Utc};
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+use crate::{header::header_str, Error, Result};
+
+/// A file in a repo, with its content as returned by gitlab
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RepoFile {
+    pub file_name: String,
+    pub file_path: String,
+    pub size: i64,
+    /// Encoding of `content`, gitlab always uses `base64`
+    pub encoding: String,
+    pub content: String,
+    pub content_sha256: String,
+    #[serde(rename = "ref")]
+    pub ref_: String,
+    pub blob_id: String,
+    pub commit_id: String,
+    pub last_commit_id: String,
+    pub execute_filemode: Option<bool>,
+}
+
+impl RepoFile {
+    /// Decode the content of the file
+    pub fn decode_content(&self) -> Result<Vec<u8>> {
+        match self.encoding.as_str() {
+            "base64" => {
+                // gitlab may wrap the encoded content
+                let content: String =
+                    self.content.split_whitespace().collect::<Vec<_>>().concat();
+                Ok(base64::decode(content)?)
+            }
+            "text" | "" => Ok(self.content.clone().into_bytes()),
+            encoding => Err(Error::Other(format!(
+                "unknown encoding {} of file {}",
+                encoding, self.file_path
+            ))),
+        }
+    }
+
+    /// Decode the content of the file as utf-8 text
+    pub fn decode_text(&self) -> Result<String> {
+        String::from_utf8(self.decode_content()?).map_err(|e| {
+            Error::Other(format!("file {} is not utf-8: {}", self.file_path, e))
+        })
+    }
+}
+
+/// Metadata of a file in a repo, read from the headers of a `HEAD` request
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FileMeta {
+    pub file_name: String,
+    pub file_path: String,
+    pub size: i64,
+    pub encoding: String,
+    pub content_sha256: String,
+    #[serde(rename = "ref")]
+    pub ref_: String,
+    pub blob_id: String,
+    pub commit_id: String,
+    pub last_commit_id: String,
+    pub execute_filemode: Option<bool>,
+}
+
+impl FileMeta {
+    pub fn from_headers(headers: &HeaderMap) -> Result<Self> {
+        let get = |name: &str| -> Result<String> {
+            header_str(headers, &format!("x-gitlab-{}", name))
+                .map(|v| v.to_string())
+                .ok_or_else(|| {
+                    Error::Other(format!("missing X-Gitlab-{} header", name))
+                })
+        };
+        let size = get("size")?;
+
+        Ok(Self {
+            file_name: get("file-name")?,
+            file_path: get("file-path")?,
+            size: size
+                .parse()
+                .map_err(|_| Error::Other(format!("invalid file size {}", size)))?,
+            encoding: get("encoding")?,
+            content_sha256: get("content-sha256")?,
+            ref_: get("ref")?,
+            blob_id: get("blob-id")?,
+            commit_id: get("commit-id")?,
+            last_commit_id: get("last-commit-id")?,
+            execute_filemode: get("execute-filemode").ok().map(|v| v == "true"),
+        })
+    }
+}
+
+/// The commit of a blame range, a subset of [`crate::commit::Commit`]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BlameCommit {
+    pub id: String,
+    #[serde(default)]
+    pub parent_ids: Vec<String>,
+    pub message: String,
+    pub author_name: String,
+    pub author_email: String,
+    pub authored_date: DateTime<Utc>,
+    pub committer_name: String,
+    pub committer_email: String,
+    pub committed_date: DateTime<Utc>,
+}
+
+/// Consecutive lines of a file last changed by the same commit
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BlameRange {
+    pub commit: BlameCommit,
+    pub lines: Vec<String>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct BlameFileOption {
+    #[serde(rename = "ref")]
+    pub ref_: String,
+    /// First line to blame, starting from 1
+    #[serde(rename = "range[start]")]
+    pub range_start: Option<i64>,
+    /// Last line to blame, inclusive
+    #[serde(rename = "range[end]")]
+    pub range_end: Option<i64>,
+}
+
+impl BlameFileOption {
+    pub fn new(ref_: &str) -> Self {
+        Self {
+            ref_: ref_.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Only blame the lines `start..=end`
+    pub fn range(mut self, start: i64, end: i64) -> Self {
+        self.range_start = Some(start);
+        self.range_end = Some(end);
+        self
+    }
+}
+
+/// The file and branch changed by a create/update request
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FileCommit {
+    pub file_path: String,
+    pub branch: String,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct CreateFileOption {
+    pub branch: String,
+    /// Create `branch` from this branch if it doesn't exist
+    pub start_branch: Option<String>,
+    /// `text` or `base64`
+    pub encoding: Option<String>,
+    pub author_email: Option<String>,
+    pub author_name: Option<String>,
+    pub content: String,
+    pub commit_message: String,
+    pub execute_filemode: Option<bool>,
+}
+
+impl CreateFileOption {
+    pub fn new(branch: &str, content: &str, commit_message: &str) -> Self {
+        Self {
+            branch: branch.to_string(),
+            content: content.to_string(),
+            commit_message: commit_message.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Create a file with binary content, sent base64 encoded
+    pub fn binary(branch: &str, content: &[u8], commit_message: &str) -> Self {
+        Self {
+            encoding: Some("base64".to_string()),
+            ..Self::new(branch, &base64::encode(content), commit_message)
+        }
+    }
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct UpdateFileOption {
+    pub branch: String,
+    /// Create `branch` from this branch if it doesn't exist
+    pub start_branch: Option<String>,
+    /// `text` or `base64`
+    pub encoding: Option<String>,
+    pub author_email: Option<String>,
+    pub author_name: Option<String>,
+    pub content: String,
+    pub commit_message: String,
+    /// Fail with a conflict if the file was changed since this commit
+    pub last_commit_id: Option<String>,
+    pub execute_filemode: Option<bool>,
+}
+
+impl UpdateFileOption {
+    pub fn new(branch: &str, content: &str, commit_message: &str) -> Self {
+        Self {
+            branch: branch.to_string(),
+            content: content.to_string(),
+            commit_message: commit_message.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Update a file with binary content, sent base64 encoded
+    pub fn binary(branch: &str, content: &[u8], commit_message: &str) -> Self {
+        Self {
+            encoding: Some("base64".to_string()),
+            ..Self::new(branch, &base64::encode(content), commit_message)
+        }
+    }
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct DeleteFileOption {
+    pub branch: String,
+    /// Create `branch` from this branch if it doesn't exist
+    pub start_branch: Option<String>,
+    pub author_email: Option<String>,
+    pub author_name: Option<String>,
+    pub commit_message: String,
+    /// Fail with a conflict if the file was changed since this commit
+    pub last_commit_id: Option<String>,
+}
+
+impl DeleteFileOption {
+    pub fn new(branch: &str, commit_message: &str) -> Self {
+        Self {
+            branch: branch.to_string(),
+            commit_message: commit_message.to_string(),
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,14 @@
+use reqwest::header::HeaderMap;
+
+/// The trimmed value of a header, `None` if it's missing or not visible ascii
+pub(crate) fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    Some(headers.get(name)?.to_str().ok()?.trim())
+}
+
+/// The value of a numeric header, `None` if it's missing or malformed
+pub(crate) fn header_num<N>(headers: &HeaderMap, name: &str) -> Option<N>
+where
+    N: std::str::FromStr,
+{
+    header_str(headers, name)?.parse().ok()
+}
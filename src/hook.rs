@@ -1,8 +1,18 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::Mutex,
+};
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
+use hmac::{Hmac, Mac, NewMac};
+use http::HeaderMap;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use serde_with::skip_serializing_none;
+use sha2::Sha256;
+use thiserror::Error as ThisError;
+
+use crate::{header::header_str, release::ReleaseAssets, Result};
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Hook {
@@ -23,14 +33,59 @@ pub struct Hook {
     pub wiki_page_events: bool,
     pub deployment_events: bool,
     pub releases_events: bool,
+    #[serde(default)]
+    pub emoji_events: bool,
+    #[serde(default)]
+    pub feature_flag_events: bool,
+    #[serde(default)]
+    pub resource_access_token_events: bool,
     pub enable_ssl_verification: bool,
     pub created_at: DateTime<Utc>,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// `wildcard`, `regex` or `all_branches`
+    pub branch_filter_strategy: Option<String>,
+    pub custom_webhook_template: Option<String>,
+    /// `executable`, `temporarily_disabled` or `disabled`
+    pub alert_status: Option<String>,
+    pub disabled_until: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub url_variables: Vec<UrlVariable>,
+    #[serde(default)]
+    pub custom_headers: Vec<CustomHeader>,
+}
+
+/// A variable substituted in the url of a hook, its value is never returned
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UrlVariable {
+    pub key: String,
+}
+
+/// A header sent with the requests of a hook, its value is never returned
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CustomHeader {
+    pub key: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UrlVariableOption {
+    pub key: String,
+    pub value: String,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CustomHeaderOption {
+    pub key: String,
+    pub value: String,
+}
+
+/// Create a project hook, the fields left to `None` take gitlab's defaults
 #[skip_serializing_none]
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CreateHookOption {
     pub url: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
     pub confidential_issues_events: Option<bool>,
     pub confidential_note_events: Option<bool>,
     pub deployment_events: Option<bool>,
@@ -46,12 +101,22 @@ pub struct CreateHookOption {
     pub tag_push_events: Option<bool>,
     pub token: Option<String>,
     pub wiki_page_events: Option<bool>,
+    pub emoji_events: Option<bool>,
+    pub feature_flag_events: Option<bool>,
+    pub resource_access_token_events: Option<bool>,
+    /// `wildcard`, `regex` or `all_branches`
+    pub branch_filter_strategy: Option<String>,
+    pub custom_webhook_template: Option<String>,
+    pub url_variables: Option<Vec<UrlVariableOption>>,
+    pub custom_headers: Option<Vec<CustomHeaderOption>>,
 }
 
 impl CreateHookOption {
     pub fn new(url: &str, token: Option<String>) -> Self {
         Self {
             url: url.to_string(),
+            name: None,
+            description: None,
             confidential_issues_events: None,
             confidential_note_events: None,
             deployment_events: None,
@@ -67,15 +132,359 @@ impl CreateHookOption {
             tag_push_events: None,
             token,
             wiki_page_events: None,
+            emoji_events: None,
+            feature_flag_events: None,
+            resource_access_token_events: None,
+            branch_filter_strategy: None,
+            custom_webhook_template: None,
+            url_variables: None,
+            custom_headers: None,
         }
     }
+
+    /// The option with the settings left to `None` set to gitlab's defaults, editing a
+    /// hook with it also resets the settings not given
+    pub fn with_defaults(&self) -> Self {
+        let flag = |value: Option<bool>, default: bool| Some(value.unwrap_or(default));
+        let text = |value: &Option<String>, default: &str| {
+            Some(value.clone().unwrap_or_else(|| default.to_string()))
+        };
+        Self {
+            url: self.url.clone(),
+            name: text(&self.name, ""),
+            description: text(&self.description, ""),
+            confidential_issues_events: flag(self.confidential_issues_events, false),
+            confidential_note_events: flag(self.confidential_note_events, false),
+            deployment_events: flag(self.deployment_events, false),
+            enable_ssl_verification: flag(self.enable_ssl_verification, true),
+            issues_events: flag(self.issues_events, false),
+            job_events: flag(self.job_events, false),
+            merge_requests_events: flag(self.merge_requests_events, false),
+            note_events: flag(self.note_events, false),
+            pipeline_events: flag(self.pipeline_events, false),
+            push_events_branch_filter: text(&self.push_events_branch_filter, ""),
+            push_events: flag(self.push_events, true),
+            releases_events: flag(self.releases_events, false),
+            tag_push_events: flag(self.tag_push_events, false),
+            token: self.token.clone(),
+            wiki_page_events: flag(self.wiki_page_events, false),
+            emoji_events: flag(self.emoji_events, false),
+            feature_flag_events: flag(self.feature_flag_events, false),
+            resource_access_token_events: flag(
+                self.resource_access_token_events,
+                false,
+            ),
+            branch_filter_strategy: text(
+                &self.branch_filter_strategy,
+                DEFAULT_BRANCH_FILTER_STRATEGY,
+            ),
+            custom_webhook_template: text(&self.custom_webhook_template, ""),
+            url_variables: self.url_variables.clone(),
+            custom_headers: self.custom_headers.clone(),
+        }
+    }
+
+    /// Whether `hook` already has the settings of this option, settings left to
+    /// `None` are expected to have gitlab's defaults, i.e. no url variables and no
+    /// custom headers. The token and the values of url variables and custom headers
+    /// are never returned by gitlab, so only the keys are compared, see
+    /// [`CreateHookOption::has_secrets`].
+    pub fn matches(&self, hook: &Hook) -> bool {
+        let opt = self.with_defaults();
+        let text = |actual: &Option<String>, default: &str| {
+            Some(actual.as_deref().unwrap_or(default).to_string())
+        };
+
+        opt.url == hook.url
+            && opt.push_events == Some(hook.push_events)
+            && opt.issues_events == Some(hook.issues_events)
+            && opt.confidential_issues_events
+                == Some(hook.confidential_issues_events.unwrap_or(false))
+            && opt.merge_requests_events == Some(hook.merge_requests_events)
+            && opt.tag_push_events == Some(hook.tag_push_events)
+            && opt.note_events == Some(hook.note_events)
+            && opt.confidential_note_events
+                == Some(hook.confidential_note_events.unwrap_or(false))
+            && opt.job_events == Some(hook.job_events)
+            && opt.pipeline_events == Some(hook.pipeline_events)
+            && opt.wiki_page_events == Some(hook.wiki_page_events)
+            && opt.deployment_events == Some(hook.deployment_events)
+            && opt.releases_events == Some(hook.releases_events)
+            && opt.emoji_events == Some(hook.emoji_events)
+            && opt.feature_flag_events == Some(hook.feature_flag_events)
+            && opt.resource_access_token_events
+                == Some(hook.resource_access_token_events)
+            && opt.enable_ssl_verification == Some(hook.enable_ssl_verification)
+            && opt.push_events_branch_filter
+                == text(&hook.push_events_branch_filter, "")
+            && opt.branch_filter_strategy
+                == text(&hook.branch_filter_strategy, DEFAULT_BRANCH_FILTER_STRATEGY)
+            && opt.custom_webhook_template == text(&hook.custom_webhook_template, "")
+            && opt.name == text(&hook.name, "")
+            && opt.description == text(&hook.description, "")
+            && self.stale_url_variables(hook).is_empty()
+            && self.stale_custom_headers(hook).is_empty()
+            && self
+                .url_variable_keys()
+                .all(|key| hook.url_variables.iter().any(|var| var.key == key))
+            && self
+                .custom_header_keys()
+                .all(|key| hook.custom_headers.iter().any(|header| header.key == key))
+    }
+
+    /// The keys of the url variables of `hook` which aren't set by this option, they
+    /// aren't removed by editing the hook
+    pub fn stale_url_variables<'a>(&self, hook: &'a Hook) -> Vec<&'a str> {
+        hook.url_variables
+            .iter()
+            .map(|var| var.key.as_str())
+            .filter(|key| !self.url_variable_keys().any(|k| k == *key))
+            .collect()
+    }
+
+    /// The keys of the custom headers of `hook` which aren't set by this option, they
+    /// aren't removed by editing the hook
+    pub fn stale_custom_headers<'a>(&self, hook: &'a Hook) -> Vec<&'a str> {
+        hook.custom_headers
+            .iter()
+            .map(|header| header.key.as_str())
+            .filter(|key| !self.custom_header_keys().any(|k| k == *key))
+            .collect()
+    }
+
+    fn url_variable_keys(&self) -> impl Iterator<Item = &str> {
+        self.url_variables
+            .iter()
+            .flatten()
+            .map(|var| var.key.as_str())
+    }
+
+    fn custom_header_keys(&self) -> impl Iterator<Item = &str> {
+        self.custom_headers
+            .iter()
+            .flatten()
+            .map(|header| header.key.as_str())
+    }
+
+    /// Whether the option sets a token, url variables or custom headers, whose values
+    /// can't be compared with an existing hook
+    pub fn has_secrets(&self) -> bool {
+        self.token.is_some()
+            || self.url_variables.is_some()
+            || self.custom_headers.is_some()
+    }
+}
+
+const DEFAULT_BRANCH_FILTER_STRATEGY: &str = "wildcard";
+
+/// Edit a project hook, the fields left to `None` are unchanged
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct EditHookOption {
+    pub url: Option<String>,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub confidential_issues_events: Option<bool>,
+    pub confidential_note_events: Option<bool>,
+    pub deployment_events: Option<bool>,
+    pub enable_ssl_verification: Option<bool>,
+    pub issues_events: Option<bool>,
+    pub job_events: Option<bool>,
+    pub merge_requests_events: Option<bool>,
+    pub note_events: Option<bool>,
+    pub pipeline_events: Option<bool>,
+    pub push_events_branch_filter: Option<String>,
+    pub push_events: Option<bool>,
+    pub releases_events: Option<bool>,
+    pub tag_push_events: Option<bool>,
+    pub token: Option<String>,
+    pub wiki_page_events: Option<bool>,
+    pub emoji_events: Option<bool>,
+    pub feature_flag_events: Option<bool>,
+    pub resource_access_token_events: Option<bool>,
+    /// `wildcard`, `regex` or `all_branches`
+    pub branch_filter_strategy: Option<String>,
+    pub custom_webhook_template: Option<String>,
+    pub url_variables: Option<Vec<UrlVariableOption>>,
+    pub custom_headers: Option<Vec<CustomHeaderOption>>,
+}
+
+/// Update every setting of a hook to the ones of a create option
+impl From<CreateHookOption> for EditHookOption {
+    fn from(opt: CreateHookOption) -> Self {
+        Self {
+            url: Some(opt.url),
+            name: opt.name,
+            description: opt.description,
+            confidential_issues_events: opt.confidential_issues_events,
+            confidential_note_events: opt.confidential_note_events,
+            deployment_events: opt.deployment_events,
+            enable_ssl_verification: opt.enable_ssl_verification,
+            issues_events: opt.issues_events,
+            job_events: opt.job_events,
+            merge_requests_events: opt.merge_requests_events,
+            note_events: opt.note_events,
+            pipeline_events: opt.pipeline_events,
+            push_events_branch_filter: opt.push_events_branch_filter,
+            push_events: opt.push_events,
+            releases_events: opt.releases_events,
+            tag_push_events: opt.tag_push_events,
+            token: opt.token,
+            wiki_page_events: opt.wiki_page_events,
+            emoji_events: opt.emoji_events,
+            feature_flag_events: opt.feature_flag_events,
+            resource_access_token_events: opt.resource_access_token_events,
+            branch_filter_strategy: opt.branch_filter_strategy,
+            custom_webhook_template: opt.custom_webhook_template,
+            url_variables: opt.url_variables,
+            custom_headers: opt.custom_headers,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookAction {
+    Created,
+    Updated,
+    Unchanged,
+}
+
+/// The result of [`crate::client::Gritlab::ensure_hook`]
+#[derive(Debug)]
+pub struct EnsuredHook {
+    pub hook: Hook,
+    pub action: HookAction,
+    /// Ids of the removed hooks with the same url
+    pub removed_duplicates: Vec<i64>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
-pub struct Project {
+pub struct GroupHook {
+    pub id: i64,
+    pub url: String,
+    pub group_id: i64,
+    pub push_events: bool,
+    pub push_events_branch_filter: Option<String>,
+    pub issues_events: bool,
+    pub confidential_issues_events: Option<bool>,
+    pub merge_requests_events: bool,
+    pub repository_update_events: bool,
+    pub tag_push_events: bool,
+    pub note_events: bool,
+    pub confidential_note_events: Option<bool>,
+    pub job_events: bool,
+    pub pipeline_events: bool,
+    pub wiki_page_events: bool,
+    pub deployment_events: bool,
+    pub releases_events: bool,
+    #[serde(default)]
+    pub subgroup_events: bool,
+    #[serde(default)]
+    pub member_events: bool,
+    pub enable_ssl_verification: bool,
+    pub created_at: DateTime<Utc>,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub branch_filter_strategy: Option<String>,
+    pub custom_webhook_template: Option<String>,
+    pub alert_status: Option<String>,
+    pub disabled_until: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub url_variables: Vec<UrlVariable>,
+    #[serde(default)]
+    pub custom_headers: Vec<CustomHeader>,
+}
+
+/// Create a group hook, which also receives the events of the projects of the group
+#[skip_serializing_none]
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateGroupHookOption {
+    #[serde(flatten)]
+    pub hook: CreateHookOption,
+    pub subgroup_events: Option<bool>,
+    pub member_events: Option<bool>,
+}
+
+impl CreateGroupHookOption {
+    pub fn new(url: &str, token: Option<String>) -> Self {
+        Self {
+            hook: CreateHookOption::new(url, token),
+            subgroup_events: None,
+            member_events: None,
+        }
+    }
+}
+
+/// Edit a group hook, the fields left to `None` are unchanged
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct EditGroupHookOption {
+    #[serde(flatten)]
+    pub hook: EditHookOption,
+    pub subgroup_events: Option<bool>,
+    pub member_events: Option<bool>,
+}
+
+/// A system hook, receiving the events of the whole instance
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SystemHook {
     pub id: i64,
+    pub url: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub push_events: bool,
+    pub tag_push_events: bool,
+    pub merge_requests_events: bool,
+    pub repository_update_events: bool,
+    pub enable_ssl_verification: bool,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub url_variables: Vec<UrlVariable>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateSystemHookOption {
+    pub url: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub token: Option<String>,
+    pub push_events: Option<bool>,
+    pub tag_push_events: Option<bool>,
+    pub merge_requests_events: Option<bool>,
+    pub repository_update_events: Option<bool>,
+    pub enable_ssl_verification: Option<bool>,
+    pub push_events_branch_filter: Option<String>,
+    pub branch_filter_strategy: Option<String>,
+    pub url_variables: Option<Vec<UrlVariableOption>>,
+}
+
+impl CreateSystemHookOption {
+    pub fn new(url: &str, token: Option<String>) -> Self {
+        Self {
+            url: url.to_string(),
+            name: None,
+            description: None,
+            token,
+            push_events: None,
+            tag_push_events: None,
+            merge_requests_events: None,
+            repository_update_events: None,
+            enable_ssl_verification: Some(true),
+            push_events_branch_filter: None,
+            branch_filter_strategy: None,
+            url_variables: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Project {
+    /// `None` for the source and target projects of merge request events, which
+    /// don't carry it
+    pub id: Option<i64>,
     pub name: String,
-    pub description: String,
+    pub description: Option<String>,
     pub web_url: String,
     pub avatar_url: Option<String>,
     pub git_ssh_url: String,
@@ -102,11 +511,16 @@ pub struct Commit {
     pub id: String,
     pub message: String,
     pub title: String,
+    #[serde(with = "datetime")]
     pub timestamp: DateTime<Utc>,
     pub url: String,
     pub author: Author,
+    /// The changed files are only sent by push events
+    #[serde(default)]
     pub added: Vec<String>,
+    #[serde(default)]
     pub modified: Vec<String>,
+    #[serde(default)]
     pub removed: Vec<String>,
 }
 
@@ -114,10 +528,14 @@ pub struct Commit {
 pub struct Repository {
     pub name: String,
     pub url: String,
-    pub description: String,
-    pub homepage: String,
+    pub description: Option<String>,
+    pub homepage: Option<String>,
+    /// The git urls and visibility are only sent by push and job events
+    #[serde(default)]
     pub git_http_url: String,
+    #[serde(default)]
     pub git_ssh_url: String,
+    #[serde(default)]
     pub visibility_level: i32,
 }
 
@@ -129,7 +547,8 @@ pub struct PushPayload {
     pub after: String,
     #[serde(rename = "ref")]
     pub ref_: String,
-    pub checkout_sha: String,
+    /// `None` when the branch or tag is deleted
+    pub checkout_sha: Option<String>,
     pub message: Option<String>,
     pub user_id: i64,
     pub user_name: String,
@@ -140,6 +559,1265 @@ pub struct PushPayload {
     pub commits: Vec<Commit>,
     pub total_commits_count: i64,
     // TODO
+    #[serde(default)]
     pub push_options: HashMap<String, String>,
     pub repository: Repository,
 }
+
+/// The kind of a webhook event, as sent in the `X-Gitlab-Event` header
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WebhookEventKind {
+    Push,
+    TagPush,
+    Issue,
+    Note,
+    MergeRequest,
+    WikiPage,
+    Pipeline,
+    Job,
+    Deployment,
+    Release,
+    Member,
+    Subgroup,
+}
+
+impl WebhookEventKind {
+    /// Parse the `X-Gitlab-Event` header, confidential events are mapped to their
+    /// regular kind
+    pub fn from_header(header: &str) -> Option<Self> {
+        match header {
+            "Push Hook" => Some(Self::Push),
+            "Tag Push Hook" => Some(Self::TagPush),
+            "Issue Hook" | "Confidential Issue Hook" => Some(Self::Issue),
+            "Note Hook" | "Confidential Note Hook" => Some(Self::Note),
+            "Merge Request Hook" => Some(Self::MergeRequest),
+            "Wiki Page Hook" => Some(Self::WikiPage),
+            "Pipeline Hook" => Some(Self::Pipeline),
+            "Job Hook" => Some(Self::Job),
+            "Deployment Hook" => Some(Self::Deployment),
+            "Release Hook" => Some(Self::Release),
+            "Member Hook" => Some(Self::Member),
+            "Subgroup Hook" => Some(Self::Subgroup),
+            _ => None,
+        }
+    }
+
+    /// The `X-Gitlab-Event` header of the non-confidential event
+    pub fn header(&self) -> &'static str {
+        match self {
+            Self::Push => "Push Hook",
+            Self::TagPush => "Tag Push Hook",
+            Self::Issue => "Issue Hook",
+            Self::Note => "Note Hook",
+            Self::MergeRequest => "Merge Request Hook",
+            Self::WikiPage => "Wiki Page Hook",
+            Self::Pipeline => "Pipeline Hook",
+            Self::Job => "Job Hook",
+            Self::Deployment => "Deployment Hook",
+            Self::Release => "Release Hook",
+            Self::Member => "Member Hook",
+            Self::Subgroup => "Subgroup Hook",
+        }
+    }
+
+    /// Parse the `object_kind` of a payload
+    pub fn from_object_kind(object_kind: &str) -> Option<Self> {
+        match object_kind {
+            "push" => Some(Self::Push),
+            "tag_push" => Some(Self::TagPush),
+            "issue" => Some(Self::Issue),
+            "note" => Some(Self::Note),
+            "merge_request" => Some(Self::MergeRequest),
+            "wiki_page" => Some(Self::WikiPage),
+            "pipeline" => Some(Self::Pipeline),
+            "build" => Some(Self::Job),
+            "deployment" => Some(Self::Deployment),
+            "release" => Some(Self::Release),
+            _ => None,
+        }
+    }
+
+    /// Parse the `event_name` of a payload without `object_kind`, i.e. member and
+    /// subgroup events
+    pub fn from_event_name(event_name: &str) -> Option<Self> {
+        match event_name {
+            "user_add_to_group"
+            | "user_remove_from_group"
+            | "user_update_for_group"
+            | "user_access_request_to_group"
+            | "user_access_request_denied_for_group" => Some(Self::Member),
+            "subgroup_create" | "subgroup_destroy" => Some(Self::Subgroup),
+            _ => None,
+        }
+    }
+}
+
+/// A webhook event, see [`WebhookEvent::from_slice`]
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum WebhookEvent {
+    Push(Box<PushPayload>),
+    TagPush(Box<PushPayload>),
+    Issue(Box<IssuePayload>),
+    Note(Box<NotePayload>),
+    MergeRequest(Box<MergeRequestPayload>),
+    WikiPage(Box<WikiPagePayload>),
+    Pipeline(Box<PipelinePayload>),
+    Job(Box<JobPayload>),
+    Deployment(Box<DeploymentPayload>),
+    Release(Box<ReleasePayload>),
+    Member(Box<MemberPayload>),
+    Subgroup(Box<SubgroupPayload>),
+    /// An event this crate doesn't model yet
+    Unknown(Value),
+}
+
+impl WebhookEvent {
+    /// Parse the body of a webhook request. The kind is read from `object_kind` or
+    /// `event_name` of the payload, falling back to the `X-Gitlab-Event` header.
+    pub fn from_slice(event_header: Option<&str>, body: &[u8]) -> Result<Self> {
+        Self::from_value(event_header, serde_json::from_slice(body)?)
+    }
+
+    pub fn from_value(event_header: Option<&str>, payload: Value) -> Result<Self> {
+        let field = |name: &str| payload.get(name).and_then(Value::as_str);
+        let kind = field("object_kind")
+            .and_then(WebhookEventKind::from_object_kind)
+            .or_else(|| field("event_name").and_then(WebhookEventKind::from_event_name))
+            .or_else(|| event_header.and_then(WebhookEventKind::from_header));
+
+        let kind = match kind {
+            Some(kind) => kind,
+            None => return Ok(Self::Unknown(payload)),
+        };
+        Ok(match kind {
+            WebhookEventKind::Push => Self::Push(serde_json::from_value(payload)?),
+            WebhookEventKind::TagPush => {
+                Self::TagPush(serde_json::from_value(payload)?)
+            }
+            WebhookEventKind::Issue => Self::Issue(serde_json::from_value(payload)?),
+            WebhookEventKind::Note => Self::Note(serde_json::from_value(payload)?),
+            WebhookEventKind::MergeRequest => {
+                Self::MergeRequest(serde_json::from_value(payload)?)
+            }
+            WebhookEventKind::WikiPage => {
+                Self::WikiPage(serde_json::from_value(payload)?)
+            }
+            WebhookEventKind::Pipeline => {
+                Self::Pipeline(serde_json::from_value(payload)?)
+            }
+            WebhookEventKind::Job => Self::Job(serde_json::from_value(payload)?),
+            WebhookEventKind::Deployment => {
+                Self::Deployment(serde_json::from_value(payload)?)
+            }
+            WebhookEventKind::Release => {
+                Self::Release(serde_json::from_value(payload)?)
+            }
+            WebhookEventKind::Member => Self::Member(serde_json::from_value(payload)?),
+            WebhookEventKind::Subgroup => {
+                Self::Subgroup(serde_json::from_value(payload)?)
+            }
+        })
+    }
+
+    /// The kind of the event, `None` for unknown events
+    pub fn kind(&self) -> Option<WebhookEventKind> {
+        Some(match self {
+            Self::Push(_) => WebhookEventKind::Push,
+            Self::TagPush(_) => WebhookEventKind::TagPush,
+            Self::Issue(_) => WebhookEventKind::Issue,
+            Self::Note(_) => WebhookEventKind::Note,
+            Self::MergeRequest(_) => WebhookEventKind::MergeRequest,
+            Self::WikiPage(_) => WebhookEventKind::WikiPage,
+            Self::Pipeline(_) => WebhookEventKind::Pipeline,
+            Self::Job(_) => WebhookEventKind::Job,
+            Self::Deployment(_) => WebhookEventKind::Deployment,
+            Self::Release(_) => WebhookEventKind::Release,
+            Self::Member(_) => WebhookEventKind::Member,
+            Self::Subgroup(_) => WebhookEventKind::Subgroup,
+            Self::Unknown(_) => return None,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct User {
+    pub id: i64,
+    pub name: String,
+    pub username: String,
+    pub avatar_url: Option<String>,
+    /// Usually redacted by gitlab
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Label {
+    pub id: i64,
+    pub title: String,
+    pub color: Option<String>,
+    pub project_id: Option<i64>,
+    #[serde(default, with = "datetime::option")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default, with = "datetime::option")]
+    pub updated_at: Option<DateTime<Utc>>,
+    pub template: Option<bool>,
+    pub description: Option<String>,
+    /// `ProjectLabel` or `GroupLabel`
+    #[serde(rename = "type")]
+    pub type_: Option<String>,
+    pub group_id: Option<i64>,
+}
+
+/// The previous and current value of a changed attribute
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Change {
+    pub previous: Option<Value>,
+    pub current: Option<Value>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct IssueAttributes {
+    pub id: i64,
+    pub iid: i64,
+    pub project_id: i64,
+    pub title: String,
+    pub description: Option<String>,
+    pub state: String,
+    /// `open`, `close`, `reopen` or `update`
+    pub action: Option<String>,
+    pub author_id: i64,
+    pub assignee_id: Option<i64>,
+    #[serde(default)]
+    pub assignee_ids: Vec<i64>,
+    pub milestone_id: Option<i64>,
+    pub updated_by_id: Option<i64>,
+    #[serde(with = "datetime")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "datetime")]
+    pub updated_at: DateTime<Utc>,
+    #[serde(default, with = "datetime::option")]
+    pub closed_at: Option<DateTime<Utc>>,
+    pub due_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub confidential: bool,
+    pub discussion_locked: Option<bool>,
+    #[serde(default)]
+    pub labels: Vec<Label>,
+    pub url: String,
+    #[serde(default)]
+    pub time_estimate: i64,
+    #[serde(default)]
+    pub total_time_spent: i64,
+    pub weight: Option<i64>,
+    pub severity: Option<String>,
+    /// `Issue`, `Incident`, `TestCase` or `Task`
+    #[serde(rename = "type")]
+    pub type_: Option<String>,
+    pub moved_to_id: Option<i64>,
+    pub duplicated_to_id: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct IssuePayload {
+    pub object_kind: String,
+    pub event_type: Option<String>,
+    pub user: User,
+    pub project: Project,
+    pub repository: Option<Repository>,
+    pub object_attributes: IssueAttributes,
+    #[serde(default)]
+    pub assignees: Vec<User>,
+    #[serde(default)]
+    pub labels: Vec<Label>,
+    #[serde(default)]
+    pub changes: HashMap<String, Change>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct MergeRequestAttributes {
+    pub id: i64,
+    pub iid: i64,
+    pub title: String,
+    pub description: Option<String>,
+    pub state: String,
+    /// e.g. `open`, `close`, `reopen`, `update`, `approved`, `unapproved` or `merge`
+    pub action: Option<String>,
+    pub source_branch: String,
+    pub source_project_id: i64,
+    pub target_branch: String,
+    pub target_project_id: i64,
+    pub author_id: i64,
+    pub assignee_id: Option<i64>,
+    #[serde(default)]
+    pub assignee_ids: Vec<i64>,
+    #[serde(default)]
+    pub reviewer_ids: Vec<i64>,
+    pub milestone_id: Option<i64>,
+    pub updated_by_id: Option<i64>,
+    #[serde(with = "datetime")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "datetime")]
+    pub updated_at: DateTime<Utc>,
+    #[serde(default, with = "datetime::option")]
+    pub last_edited_at: Option<DateTime<Utc>>,
+    pub merge_status: String,
+    pub detailed_merge_status: Option<String>,
+    pub merge_commit_sha: Option<String>,
+    #[serde(default)]
+    pub merge_when_pipeline_succeeds: bool,
+    /// The previous head when new commits are pushed
+    pub oldrev: Option<String>,
+    pub head_pipeline_id: Option<i64>,
+    #[serde(default)]
+    pub draft: bool,
+    #[serde(default)]
+    pub work_in_progress: bool,
+    pub blocking_discussions_resolved: Option<bool>,
+    pub first_contribution: Option<bool>,
+    #[serde(default)]
+    pub labels: Vec<Label>,
+    pub url: String,
+    pub source: Project,
+    pub target: Project,
+    pub last_commit: Commit,
+    #[serde(default)]
+    pub time_estimate: i64,
+    #[serde(default)]
+    pub total_time_spent: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct MergeRequestPayload {
+    pub object_kind: String,
+    pub event_type: Option<String>,
+    pub user: User,
+    pub project: Project,
+    pub repository: Option<Repository>,
+    pub object_attributes: MergeRequestAttributes,
+    #[serde(default)]
+    pub assignees: Vec<User>,
+    #[serde(default)]
+    pub reviewers: Vec<User>,
+    #[serde(default)]
+    pub labels: Vec<Label>,
+    #[serde(default)]
+    pub changes: HashMap<String, Change>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct NoteAttributes {
+    pub id: i64,
+    pub note: String,
+    /// `Commit`, `MergeRequest`, `Issue` or `Snippet`
+    pub noteable_type: String,
+    pub noteable_id: Option<i64>,
+    pub author_id: i64,
+    pub project_id: Option<i64>,
+    #[serde(with = "datetime")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "datetime")]
+    pub updated_at: DateTime<Utc>,
+    pub attachment: Option<String>,
+    pub line_code: Option<String>,
+    pub commit_id: Option<String>,
+    pub discussion_id: Option<String>,
+    #[serde(default)]
+    pub system: bool,
+    /// The diff position of a comment on a diff line
+    pub position: Option<Value>,
+    /// e.g. `DiscussionNote` or `DiffNote`
+    #[serde(rename = "type")]
+    pub type_: Option<String>,
+    /// `create` or `update`
+    pub action: Option<String>,
+    pub url: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Snippet {
+    pub id: i64,
+    pub title: String,
+    pub description: Option<String>,
+    pub content: Option<String>,
+    pub author_id: i64,
+    pub project_id: Option<i64>,
+    #[serde(with = "datetime")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "datetime")]
+    pub updated_at: DateTime<Utc>,
+    pub file_name: Option<String>,
+    pub visibility_level: Option<i32>,
+    pub url: Option<String>,
+}
+
+/// A comment, the noteable it was made on is set according to `noteable_type`
+#[derive(Debug, Deserialize, Serialize)]
+pub struct NotePayload {
+    pub object_kind: String,
+    pub event_type: Option<String>,
+    pub user: User,
+    pub project_id: i64,
+    pub project: Project,
+    pub repository: Option<Repository>,
+    pub object_attributes: NoteAttributes,
+    pub commit: Option<Commit>,
+    pub merge_request: Option<MergeRequestAttributes>,
+    pub issue: Option<IssueAttributes>,
+    pub snippet: Option<Snippet>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Wiki {
+    pub web_url: String,
+    pub git_ssh_url: String,
+    pub git_http_url: String,
+    pub path_with_namespace: String,
+    pub default_branch: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct WikiPageAttributes {
+    pub title: String,
+    pub content: Option<String>,
+    pub format: String,
+    pub message: Option<String>,
+    pub slug: String,
+    pub url: String,
+    /// `create`, `update` or `delete`
+    pub action: String,
+    pub diff_url: Option<String>,
+    pub version_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct WikiPagePayload {
+    pub object_kind: String,
+    pub user: User,
+    pub project: Project,
+    pub wiki: Wiki,
+    pub object_attributes: WikiPageAttributes,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Variable {
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PipelineAttributes {
+    pub id: i64,
+    pub iid: Option<i64>,
+    pub name: Option<String>,
+    #[serde(rename = "ref")]
+    pub ref_: String,
+    pub tag: bool,
+    pub sha: String,
+    pub before_sha: String,
+    pub source: String,
+    pub status: String,
+    pub detailed_status: Option<String>,
+    #[serde(default)]
+    pub stages: Vec<String>,
+    #[serde(with = "datetime")]
+    pub created_at: DateTime<Utc>,
+    #[serde(default, with = "datetime::option")]
+    pub finished_at: Option<DateTime<Utc>>,
+    pub duration: Option<i64>,
+    pub queued_duration: Option<i64>,
+    #[serde(default)]
+    pub variables: Vec<Variable>,
+    pub url: Option<String>,
+}
+
+/// The merge request of a merge request pipeline
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PipelineMergeRequest {
+    pub id: i64,
+    pub iid: i64,
+    pub title: String,
+    pub source_branch: String,
+    pub source_project_id: i64,
+    pub target_branch: String,
+    pub target_project_id: i64,
+    pub state: String,
+    pub merge_status: Option<String>,
+    pub detailed_merge_status: Option<String>,
+    pub url: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Runner {
+    pub id: i64,
+    pub description: Option<String>,
+    /// `instance_type`, `group_type` or `project_type`
+    pub runner_type: Option<String>,
+    pub active: bool,
+    pub is_shared: bool,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ArtifactsFile {
+    pub filename: Option<String>,
+    pub size: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Environment {
+    pub name: String,
+    /// `start`, `prepare`, `stop`, `verify` or `access`
+    pub action: Option<String>,
+    pub deployment_tier: Option<String>,
+}
+
+/// A job of a pipeline event
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PipelineBuild {
+    pub id: i64,
+    pub stage: String,
+    pub name: String,
+    pub status: String,
+    #[serde(with = "datetime")]
+    pub created_at: DateTime<Utc>,
+    #[serde(default, with = "datetime::option")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(default, with = "datetime::option")]
+    pub finished_at: Option<DateTime<Utc>>,
+    pub duration: Option<f64>,
+    pub queued_duration: Option<f64>,
+    pub failure_reason: Option<String>,
+    pub when: String,
+    pub manual: bool,
+    pub allow_failure: bool,
+    pub user: Option<User>,
+    pub runner: Option<Runner>,
+    pub artifacts_file: Option<ArtifactsFile>,
+    pub environment: Option<Environment>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PipelinePayload {
+    pub object_kind: String,
+    pub object_attributes: PipelineAttributes,
+    pub merge_request: Option<PipelineMergeRequest>,
+    pub user: User,
+    pub project: Project,
+    pub commit: Option<Commit>,
+    /// The upstream pipeline of a multi-project pipeline
+    pub source_pipeline: Option<Value>,
+    #[serde(default)]
+    pub builds: Vec<PipelineBuild>,
+}
+
+/// The pipeline and commit of a job event
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JobCommit {
+    /// The id of the pipeline
+    pub id: i64,
+    pub name: Option<String>,
+    pub sha: String,
+    pub message: String,
+    pub author_name: String,
+    pub author_email: String,
+    pub author_url: Option<String>,
+    pub status: String,
+    pub duration: Option<i64>,
+    #[serde(default, with = "datetime::option")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(default, with = "datetime::option")]
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JobPayload {
+    pub object_kind: String,
+    #[serde(rename = "ref")]
+    pub ref_: String,
+    pub tag: bool,
+    pub before_sha: String,
+    pub sha: String,
+    pub build_id: i64,
+    pub build_name: String,
+    pub build_stage: String,
+    pub build_status: String,
+    #[serde(with = "datetime")]
+    pub build_created_at: DateTime<Utc>,
+    #[serde(default, with = "datetime::option")]
+    pub build_started_at: Option<DateTime<Utc>>,
+    #[serde(default, with = "datetime::option")]
+    pub build_finished_at: Option<DateTime<Utc>>,
+    pub build_duration: Option<f64>,
+    pub build_queued_duration: Option<f64>,
+    pub build_allow_failure: bool,
+    pub build_failure_reason: Option<String>,
+    pub pipeline_id: i64,
+    pub runner: Option<Runner>,
+    pub project_id: i64,
+    pub project_name: String,
+    pub user: User,
+    pub commit: JobCommit,
+    pub repository: Repository,
+    pub project: Option<Project>,
+    pub environment: Option<Environment>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct DeploymentPayload {
+    pub object_kind: String,
+    /// `running`, `success`, `failed` or `canceled`
+    pub status: String,
+    #[serde(with = "datetime")]
+    pub status_changed_at: DateTime<Utc>,
+    pub deployment_id: i64,
+    pub deployable_id: Option<i64>,
+    pub deployable_url: Option<String>,
+    pub environment: String,
+    pub environment_tier: Option<String>,
+    pub environment_slug: Option<String>,
+    pub environment_external_url: Option<String>,
+    pub project: Project,
+    pub short_sha: String,
+    pub user: User,
+    pub user_url: String,
+    pub commit_url: String,
+    pub commit_title: String,
+    #[serde(rename = "ref")]
+    pub ref_: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ReleasePayload {
+    pub object_kind: String,
+    pub id: i64,
+    #[serde(with = "datetime")]
+    pub created_at: DateTime<Utc>,
+    pub description: Option<String>,
+    pub name: String,
+    #[serde(with = "datetime")]
+    pub released_at: DateTime<Utc>,
+    pub tag: String,
+    pub project: Project,
+    pub url: String,
+    /// `create`, `update` or `delete`
+    pub action: String,
+    pub assets: ReleaseAssets,
+    pub commit: Commit,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct MemberPayload {
+    /// e.g. `user_add_to_group`, `user_update_for_group` or `user_remove_from_group`
+    pub event_name: String,
+    #[serde(with = "datetime")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "datetime")]
+    pub updated_at: DateTime<Utc>,
+    pub group_name: String,
+    pub group_path: String,
+    pub group_id: i64,
+    pub user_username: String,
+    pub user_name: String,
+    pub user_email: Option<String>,
+    pub user_id: i64,
+    /// e.g. `Guest`, `Developer` or `Maintainer`
+    pub group_access: String,
+    pub group_plan: Option<String>,
+    #[serde(default, with = "datetime::option")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SubgroupPayload {
+    /// `subgroup_create` or `subgroup_destroy`
+    pub event_name: String,
+    #[serde(with = "datetime")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "datetime")]
+    pub updated_at: DateTime<Utc>,
+    pub name: String,
+    pub path: String,
+    pub full_path: String,
+    pub group_id: i64,
+    pub parent_group_id: i64,
+    pub parent_name: String,
+    pub parent_path: String,
+    pub parent_full_path: String,
+}
+
+pub const TOKEN_HEADER: &str = "X-Gitlab-Token";
+pub const EVENT_HEADER: &str = "X-Gitlab-Event";
+pub const EVENT_UUID_HEADER: &str = "X-Gitlab-Event-UUID";
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Why a webhook request was rejected by [`WebhookVerifier::verify`]
+#[derive(Debug, Clone, PartialEq, Eq, ThisError)]
+pub enum WebhookRejection {
+    #[error("missing X-Gitlab-Token header")]
+    MissingToken,
+
+    #[error("invalid X-Gitlab-Token header")]
+    InvalidToken,
+
+    #[error("missing X-Gitlab-Event header")]
+    MissingEvent,
+
+    #[error("malformed X-Gitlab-Event header")]
+    MalformedEvent,
+
+    #[error("X-Gitlab-Event header {header:?} doesn't match the payload {payload:?}")]
+    EventMismatch {
+        header: WebhookEventKind,
+        payload: Option<WebhookEventKind>,
+    },
+
+    #[error("duplicate delivery {0}")]
+    Duplicate(String),
+
+    #[error("invalid payload: {0}")]
+    InvalidPayload(String),
+}
+
+/// Verify and parse incoming webhook requests, independent of the http server.
+///
+/// The secret is the `token` of [`CreateHookOption`], deliveries are deduplicated
+/// by their `Idempotency-Key` or `X-Gitlab-Event-UUID` header when enabled.
+#[derive(Debug, Default)]
+pub struct WebhookVerifier {
+    secret: Option<String>,
+    dedup: Option<Mutex<DeliveryCache>>,
+}
+
+#[derive(Debug, Default)]
+struct DeliveryCache {
+    capacity: usize,
+    order: VecDeque<String>,
+    seen: HashSet<String>,
+}
+
+impl DeliveryCache {
+    /// Returns false if the delivery was already seen
+    fn insert(&mut self, id: &str) -> bool {
+        if !self.seen.insert(id.to_string()) {
+            return false;
+        }
+        self.order.push_back(id.to_string());
+        if self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+impl WebhookVerifier {
+    /// Requests without a matching `X-Gitlab-Token` are rejected if `secret` is set
+    pub fn new(secret: Option<&str>) -> Self {
+        Self {
+            secret: secret.map(|s| s.to_string()),
+            dedup: None,
+        }
+    }
+
+    /// Reject deliveries already seen among the last `capacity` ones
+    pub fn dedup(mut self, capacity: usize) -> Self {
+        self.dedup = Some(Mutex::new(DeliveryCache {
+            capacity,
+            ..Default::default()
+        }));
+        self
+    }
+
+    /// Verify the headers of a webhook request and parse its body, then record the
+    /// delivery to reject its duplicates
+    pub fn verify(
+        &self,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> std::result::Result<WebhookEvent, WebhookRejection> {
+        let event = self.verify_payload(headers, body)?;
+        // only remember valid deliveries, so that a rejected one may be redelivered
+        self.record_delivery(headers)?;
+        Ok(event)
+    }
+
+    /// Like [`WebhookVerifier::verify`] without recording the delivery, e.g. to
+    /// record it with [`WebhookVerifier::record_delivery`] only once the event is
+    /// sure to be handled
+    pub fn verify_payload(
+        &self,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> std::result::Result<WebhookEvent, WebhookRejection> {
+        let header = |name: &str| header_str(headers, name);
+
+        if let Some(secret) = &self.secret {
+            let token = header(TOKEN_HEADER).ok_or(WebhookRejection::MissingToken)?;
+            if !token_eq(secret, token) {
+                return Err(WebhookRejection::InvalidToken);
+            }
+        }
+
+        if !headers.contains_key(EVENT_HEADER) {
+            return Err(WebhookRejection::MissingEvent);
+        }
+        let event_header = header(EVENT_HEADER)
+            .filter(|event_header| !event_header.is_empty())
+            .ok_or(WebhookRejection::MalformedEvent)?;
+
+        // system hooks send all kinds of events, the payload tells which one. Events
+        // this crate doesn't model yet are passed through as `WebhookEvent::Unknown`.
+        let expected = WebhookEventKind::from_header(event_header);
+        let event = match (event_header, expected) {
+            ("System Hook", _) | (_, Some(_)) => {
+                WebhookEvent::from_slice(Some(event_header), body)
+            }
+            (_, None) => serde_json::from_slice(body)
+                .map(WebhookEvent::Unknown)
+                .map_err(Into::into),
+        }
+        .map_err(|e| WebhookRejection::InvalidPayload(e.to_string()))?;
+        if let Some(expected) = expected {
+            if event.kind() != Some(expected) {
+                return Err(WebhookRejection::EventMismatch {
+                    header: expected,
+                    payload: event.kind(),
+                });
+            }
+        }
+
+        Ok(event)
+    }
+
+    /// Remember the delivery of a request, fails with
+    /// [`WebhookRejection::Duplicate`] if it was already seen. Does nothing unless
+    /// dedup is enabled.
+    pub fn record_delivery(
+        &self,
+        headers: &HeaderMap,
+    ) -> std::result::Result<(), WebhookRejection> {
+        let header = |name: &str| header_str(headers, name);
+        if let (Some(dedup), Some(id)) = (
+            &self.dedup,
+            header(IDEMPOTENCY_KEY_HEADER).or_else(|| header(EVENT_UUID_HEADER)),
+        ) {
+            let mut dedup = dedup.lock().unwrap_or_else(|e| e.into_inner());
+            if !dedup.insert(id) {
+                return Err(WebhookRejection::Duplicate(id.to_string()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compare tokens in constant time by comparing their hmac, which also hides their
+/// length
+fn token_eq(secret: &str, token: &str) -> bool {
+    let mac = |data: &str| {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+            .expect("hmac accepts keys of any length");
+        mac.update(data.as_bytes());
+        mac
+    };
+    mac(token)
+        .verify(&mac(secret).finalize().into_bytes())
+        .is_ok()
+}
+
+/// Webhook payloads use both rfc3339 and `2016-08-12 15:23:28 UTC` style timestamps
+mod datetime {
+    use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    fn parse(s: &str) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(s)
+            .or_else(|_| DateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S %z"))
+            .map(|dt| dt.with_timezone(&Utc))
+            .ok()
+            .or_else(|| {
+                NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S UTC")
+                    .ok()
+                    .map(|dt| Utc.from_utc_datetime(&dt))
+            })
+    }
+
+    pub fn serialize<S>(dt: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        dt.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        parse(&s).ok_or_else(|| D::Error::custom(format!("invalid datetime {}", s)))
+    }
+
+    pub mod option {
+        use super::*;
+
+        pub fn serialize<S>(
+            dt: &Option<DateTime<Utc>>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            dt.serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D>(
+            deserializer: D,
+        ) -> Result<Option<DateTime<Utc>>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            match Option::<String>::deserialize(deserializer)? {
+                Some(s) => parse(&s)
+                    .map(Some)
+                    .ok_or_else(|| D::Error::custom(format!("invalid datetime {}", s))),
+                None => Ok(None),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use http::HeaderValue;
+    use serde_json::json;
+
+    use super::*;
+
+    fn fixture(kind: WebhookEventKind, body: &str) -> WebhookEvent {
+        let event = WebhookEvent::from_slice(Some(kind.header()), body.as_bytes())
+            .unwrap_or_else(|e| panic!("parse {:?} fixture: {}", kind, e));
+        assert_eq!(event.kind(), Some(kind));
+        event
+    }
+
+    macro_rules! fixture {
+        ($kind:ident, $file:literal) => {
+            match fixture(
+                WebhookEventKind::$kind,
+                include_str!(concat!("../tests/fixtures/webhook/", $file)),
+            ) {
+                WebhookEvent::$kind(payload) => payload,
+                event => panic!("unexpected event {:?}", event),
+            }
+        };
+    }
+
+    #[test]
+    fn parse_push() {
+        let payload = fixture!(Push, "push.json");
+        assert_eq!(payload.ref_, "refs/heads/master");
+        assert_eq!(payload.commits.len(), 2);
+        assert_eq!(payload.commits[0].added, ["CHANGELOG"]);
+    }
+
+    #[test]
+    fn parse_tag_push() {
+        let payload = fixture!(TagPush, "tag_push.json");
+        assert_eq!(payload.ref_, "refs/tags/v1.0.0");
+        assert!(payload.commits.is_empty());
+    }
+
+    #[test]
+    fn parse_issue() {
+        let payload = fixture!(Issue, "issue.json");
+        assert_eq!(payload.object_attributes.iid, 23);
+        assert_eq!(payload.object_attributes.action.as_deref(), Some("open"));
+        assert_eq!(payload.labels[0].title, "API");
+        assert!(payload.changes.contains_key("labels"));
+    }
+
+    #[test]
+    fn parse_note() {
+        let payload = fixture!(Note, "note.json");
+        assert_eq!(payload.object_attributes.noteable_type, "Commit");
+        assert!(payload.commit.is_some());
+        assert!(payload.merge_request.is_none());
+    }
+
+    #[test]
+    fn parse_merge_request() {
+        let payload = fixture!(MergeRequest, "merge_request.json");
+        assert_eq!(payload.object_attributes.source_branch, "ms-viewport");
+        assert_eq!(payload.object_attributes.reviewer_ids, [6]);
+        assert_eq!(payload.project.id, Some(1));
+        assert_eq!(payload.object_attributes.source.id, None);
+        assert_eq!(payload.reviewers[0].username, "user1");
+    }
+
+    #[test]
+    fn parse_wiki_page() {
+        let payload = fixture!(WikiPage, "wiki_page.json");
+        assert_eq!(payload.object_attributes.slug, "awesome");
+        assert_eq!(payload.object_attributes.action, "create");
+    }
+
+    #[test]
+    fn parse_pipeline() {
+        let payload = fixture!(Pipeline, "pipeline.json");
+        assert_eq!(payload.object_attributes.status, "success");
+        assert_eq!(payload.builds.len(), 2);
+        assert!(payload.merge_request.is_some());
+        assert_eq!(
+            payload.object_attributes.finished_at,
+            Some(Utc.ymd(2016, 8, 12).and_hms(15, 26, 29))
+        );
+    }
+
+    #[test]
+    fn parse_job() {
+        let payload = fixture!(Job, "build.json");
+        assert_eq!(payload.build_id, 1977);
+        assert_eq!(payload.commit.id, 2366);
+        assert!(payload.build_started_at.is_none());
+    }
+
+    #[test]
+    fn parse_deployment() {
+        let payload = fixture!(Deployment, "deployment.json");
+        assert_eq!(payload.environment, "staging");
+        assert_eq!(
+            payload.status_changed_at,
+            Utc.ymd(2021, 4, 28).and_hms(19, 50, 0)
+        );
+    }
+
+    #[test]
+    fn parse_release() {
+        let payload = fixture!(Release, "release.json");
+        assert_eq!(payload.tag, "v1.1");
+        assert_eq!(payload.assets.sources.len(), 2);
+    }
+
+    #[test]
+    fn parse_member() {
+        let payload = fixture!(Member, "member.json");
+        assert_eq!(payload.event_name, "user_add_to_group");
+        assert_eq!(payload.group_access, "Guest");
+    }
+
+    #[test]
+    fn parse_subgroup() {
+        let payload = fixture!(Subgroup, "subgroup.json");
+        assert_eq!(payload.full_path, "group1/subgroup1");
+    }
+
+    #[test]
+    fn parse_datetime() {
+        let expected = Utc.ymd(2021, 1, 1).and_hms(0, 0, 0);
+        for s in [
+            "2021-01-01 00:00:00 UTC",
+            "2021-01-01T00:00:00Z",
+            "2021-01-01T08:00:00+08:00",
+            "2021-01-01 01:00:00 +0100",
+        ] {
+            assert_eq!(datetime::deserialize(json!(s)).unwrap(), expected, "{}", s);
+        }
+        assert!(datetime::deserialize(json!("yesterday")).is_err());
+        assert_eq!(datetime::option::deserialize(json!(null)).unwrap(), None);
+    }
+
+    const PUSH: &str = include_str!("../tests/fixtures/webhook/push.json");
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    fn push_headers(token: &'static str, uuid: &'static str) -> HeaderMap {
+        headers(&[
+            (EVENT_HEADER, "Push Hook"),
+            (TOKEN_HEADER, token),
+            (EVENT_UUID_HEADER, uuid),
+        ])
+    }
+
+    #[test]
+    fn verify_token() {
+        let verifier = WebhookVerifier::new(Some("secret"));
+        let body = PUSH.as_bytes();
+
+        let event = verifier.verify(&push_headers("secret", "1"), body).unwrap();
+        assert_eq!(event.kind(), Some(WebhookEventKind::Push));
+        assert_eq!(
+            verifier
+                .verify(&push_headers("secreT", "2"), body)
+                .unwrap_err(),
+            WebhookRejection::InvalidToken
+        );
+        assert_eq!(
+            verifier
+                .verify(&headers(&[(EVENT_HEADER, "Push Hook")]), body)
+                .unwrap_err(),
+            WebhookRejection::MissingToken
+        );
+        // without a secret the token isn't checked
+        assert!(WebhookVerifier::new(None)
+            .verify(&headers(&[(EVENT_HEADER, "Push Hook")]), body)
+            .is_ok());
+    }
+
+    #[test]
+    fn verify_event_header() {
+        let verifier = WebhookVerifier::new(None);
+
+        let event = verifier
+            .verify(
+                &headers(&[(EVENT_HEADER, "Emoji Hook")]),
+                br#"{"object_kind": "emoji", "event_type": "award"}"#,
+            )
+            .unwrap();
+        assert!(matches!(event, WebhookEvent::Unknown(_)));
+
+        let event = verifier
+            .verify(&headers(&[(EVENT_HEADER, "System Hook")]), PUSH.as_bytes())
+            .unwrap();
+        assert_eq!(event.kind(), Some(WebhookEventKind::Push));
+
+        assert_eq!(
+            verifier
+                .verify(
+                    &headers(&[(EVENT_HEADER, "Tag Push Hook")]),
+                    PUSH.as_bytes()
+                )
+                .unwrap_err(),
+            WebhookRejection::EventMismatch {
+                header: WebhookEventKind::TagPush,
+                payload: Some(WebhookEventKind::Push),
+            }
+        );
+        assert_eq!(
+            verifier.verify(&headers(&[]), PUSH.as_bytes()).unwrap_err(),
+            WebhookRejection::MissingEvent
+        );
+        assert_eq!(
+            verifier
+                .verify(&headers(&[(EVENT_HEADER, "")]), PUSH.as_bytes())
+                .unwrap_err(),
+            WebhookRejection::MalformedEvent
+        );
+    }
+
+    #[test]
+    fn verify_duplicates() {
+        let verifier = WebhookVerifier::new(None).dedup(8);
+        let body = PUSH.as_bytes();
+
+        assert!(verifier.verify(&push_headers("", "uuid-1"), body).is_ok());
+        assert_eq!(
+            verifier
+                .verify(&push_headers("", "uuid-1"), body)
+                .unwrap_err(),
+            WebhookRejection::Duplicate("uuid-1".to_string())
+        );
+
+        // the idempotency key takes precedence over the event uuid
+        let retried = |uuid| {
+            let mut headers = push_headers("", uuid);
+            headers.insert(IDEMPOTENCY_KEY_HEADER, HeaderValue::from_static("key-1"));
+            headers
+        };
+        assert!(verifier.verify(&retried("uuid-2"), body).is_ok());
+        assert_eq!(
+            verifier.verify(&retried("uuid-3"), body).unwrap_err(),
+            WebhookRejection::Duplicate("key-1".to_string())
+        );
+
+        // a rejected delivery isn't remembered
+        let mut invalid = push_headers("", "uuid-4");
+        invalid.insert(EVENT_HEADER, HeaderValue::from_static("Tag Push Hook"));
+        assert!(verifier.verify(&invalid, body).is_err());
+        assert!(verifier.verify(&push_headers("", "uuid-4"), body).is_ok());
+    }
+
+    #[test]
+    fn delivery_cache_evicts_oldest() {
+        let mut cache = DeliveryCache {
+            capacity: 2,
+            ..Default::default()
+        };
+        assert!(cache.insert("a"));
+        assert!(cache.insert("b"));
+        assert!(!cache.insert("a"));
+        assert!(cache.insert("c"));
+        // inserting `c` evicted `a`, the oldest delivery
+        assert!(cache.insert("a"));
+        assert!(!cache.insert("c"));
+        assert_eq!(cache.order.len(), 2);
+    }
+
+    #[test]
+    fn hook_option_matches_defaults() {
+        let hook: Hook = serde_json::from_value(json!({
+            "id": 1,
+            "url": "https://example.com/hook",
+            "project_id": 3,
+            "push_events": true,
+            "push_events_branch_filter": "",
+            "issues_events": false,
+            "confidential_issues_events": false,
+            "merge_requests_events": true,
+            "repository_update_events": false,
+            "tag_push_events": false,
+            "note_events": false,
+            "confidential_note_events": null,
+            "job_events": false,
+            "pipeline_events": false,
+            "wiki_page_events": false,
+            "deployment_events": false,
+            "releases_events": false,
+            "enable_ssl_verification": true,
+            "created_at": "2012-10-12T17:04:47Z",
+            "name": null,
+            "branch_filter_strategy": "wildcard"
+        }))
+        .unwrap();
+
+        let mut opt = CreateHookOption::new("https://example.com/hook", None);
+        // merge request events are off by default
+        assert!(!opt.matches(&hook));
+        opt.merge_requests_events = Some(true);
+        assert!(opt.matches(&hook));
+        opt.name = Some("ci".to_string());
+        assert!(!opt.matches(&hook));
+        opt.name = None;
+        opt.emoji_events = Some(true);
+        assert!(!opt.matches(&hook));
+        opt.emoji_events = None;
+
+        let mut drifted: Hook =
+            serde_json::from_value(serde_json::to_value(&hook).unwrap()).unwrap();
+        drifted.url_variables.push(UrlVariable {
+            key: "stale".to_string(),
+        });
+        assert!(!opt.matches(&drifted));
+        assert_eq!(opt.stale_url_variables(&drifted), ["stale"]);
+        opt.url_variables = Some(vec![UrlVariableOption {
+            key: "stale".to_string(),
+            value: "v".to_string(),
+        }]);
+        assert!(opt.matches(&drifted));
+        assert!(opt.stale_url_variables(&drifted).is_empty());
+        assert!(!opt.matches(&hook));
+
+        let opt = CreateHookOption::new("https://example.com/hook", Some("t".into()));
+        assert!(opt.has_secrets());
+        let opt = opt.with_defaults();
+        assert_eq!(opt.issues_events, Some(false));
+        assert_eq!(opt.branch_filter_strategy.as_deref(), Some("wildcard"));
+    }
+}
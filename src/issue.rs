@@ -0,0 +1,159 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+use crate::{
+    merge_request::{LabelRef, Milestone, References, TaskCompletionStatus, TimeStats},
+    user::BasicUser,
+};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Issue {
+    pub id: i64,
+    pub iid: i64,
+    pub project_id: i64,
+    pub title: String,
+    pub description: Option<String>,
+    pub state: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub closed_by: Option<BasicUser>,
+    pub labels: Vec<LabelRef>,
+    pub milestone: Option<Milestone>,
+    #[serde(default)]
+    pub assignees: Vec<BasicUser>,
+    pub assignee: Option<BasicUser>,
+    pub author: BasicUser,
+    /// `ISSUE`, `INCIDENT`, `TEST_CASE` or `TASK`
+    #[serde(rename = "type")]
+    pub type_: Option<String>,
+    pub issue_type: Option<String>,
+    pub user_notes_count: i64,
+    pub merge_requests_count: Option<i64>,
+    pub upvotes: i64,
+    pub downvotes: i64,
+    pub due_date: Option<NaiveDate>,
+    pub confidential: bool,
+    pub discussion_locked: Option<bool>,
+    pub web_url: String,
+    pub time_stats: Option<TimeStats>,
+    pub task_completion_status: Option<TaskCompletionStatus>,
+    pub weight: Option<i64>,
+    pub has_tasks: Option<bool>,
+    pub references: Option<References>,
+    pub severity: Option<String>,
+    pub subscribed: Option<bool>,
+    pub moved_to_id: Option<i64>,
+}
+
+/// An issue linked to another one
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LinkedIssue {
+    #[serde(flatten)]
+    pub issue: Issue,
+    pub issue_link_id: i64,
+    /// `relates_to`, `blocks` or `is_blocked_by`
+    pub link_type: String,
+    pub link_created_at: Option<DateTime<Utc>>,
+    pub link_updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct IssueLink {
+    pub source_issue: Issue,
+    pub target_issue: Issue,
+    pub link_type: String,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ListIssuesOption {
+    /// `opened` or `closed`
+    pub state: Option<String>,
+    /// Comma-separated label names, `None` or `Any`
+    pub labels: Option<String>,
+    pub with_labels_details: Option<bool>,
+    /// Milestone title, `None`, `Any`, `Upcoming` or `Started`
+    pub milestone: Option<String>,
+    /// `created_by_me`, `assigned_to_me` or `all`
+    pub scope: Option<String>,
+    pub author_id: Option<i64>,
+    pub author_username: Option<String>,
+    pub assignee_id: Option<i64>,
+    pub assignee_username: Option<String>,
+    pub my_reaction_emoji: Option<String>,
+    pub weight: Option<i64>,
+    pub confidential: Option<bool>,
+    pub issue_type: Option<String>,
+    pub order_by: Option<String>,
+    pub sort: Option<String>,
+    pub search: Option<String>,
+    #[serde(rename = "in")]
+    pub in_: Option<String>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    pub updated_after: Option<DateTime<Utc>>,
+    pub updated_before: Option<DateTime<Utc>>,
+    /// `0`, `any`, `today`, `tomorrow`, `overdue`, `week` or `month`
+    pub due_date: Option<String>,
+    pub non_archived: Option<bool>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct CreateIssueOption {
+    pub title: String,
+    pub description: Option<String>,
+    pub confidential: Option<bool>,
+    pub assignee_ids: Option<Vec<i64>>,
+    pub milestone_id: Option<i64>,
+    /// Comma-separated label names
+    pub labels: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub due_date: Option<NaiveDate>,
+    pub weight: Option<i64>,
+    pub issue_type: Option<String>,
+    pub merge_request_to_resolve_discussions_of: Option<i64>,
+    pub discussion_to_resolve: Option<String>,
+}
+
+impl CreateIssueOption {
+    pub fn new(title: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct EditIssueOption {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub confidential: Option<bool>,
+    pub assignee_ids: Option<Vec<i64>>,
+    pub milestone_id: Option<i64>,
+    /// Comma-separated label names
+    pub labels: Option<String>,
+    pub add_labels: Option<String>,
+    pub remove_labels: Option<String>,
+    /// `close` or `reopen`
+    pub state_event: Option<String>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub due_date: Option<NaiveDate>,
+    pub weight: Option<i64>,
+    pub discussion_locked: Option<bool>,
+    pub issue_type: Option<String>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct CreateIssueLinkOption {
+    /// Id or url-encoded path of the project of the target issue
+    pub target_project_id: String,
+    pub target_issue_iid: i64,
+    /// `relates_to`, `blocks` or `is_blocked_by`
+    pub link_type: Option<String>,
+}
@@ -0,0 +1,101 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+use crate::{commit::Commit, user::BasicUser};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct JobPipeline {
+    pub id: i64,
+    pub project_id: i64,
+    #[serde(rename = "ref")]
+    pub ref_: String,
+    pub sha: String,
+    pub status: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct JobArtifact {
+    pub file_type: String,
+    pub size: i64,
+    pub filename: String,
+    pub file_format: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ArtifactsFile {
+    pub filename: String,
+    pub size: i64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct JobRunner {
+    pub id: i64,
+    pub description: Option<String>,
+    pub active: bool,
+    pub is_shared: bool,
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Job {
+    pub id: i64,
+    pub name: String,
+    pub stage: String,
+    pub status: String,
+    #[serde(rename = "ref")]
+    pub ref_: String,
+    pub tag: bool,
+    pub coverage: Option<f64>,
+    pub allow_failure: bool,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub erased_at: Option<DateTime<Utc>>,
+    pub duration: Option<f64>,
+    pub queued_duration: Option<f64>,
+    pub user: Option<BasicUser>,
+    pub commit: Option<Commit>,
+    pub pipeline: JobPipeline,
+    pub web_url: String,
+    #[serde(default)]
+    pub artifacts: Vec<JobArtifact>,
+    pub artifacts_file: Option<ArtifactsFile>,
+    pub artifacts_expire_at: Option<DateTime<Utc>>,
+    pub runner: Option<JobRunner>,
+    #[serde(default)]
+    pub tag_list: Vec<String>,
+    pub failure_reason: Option<String>,
+}
+
+impl Job {
+    /// Whether the job has reached a final status, i.e. its trace won't grow anymore
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self.status.as_str(),
+            "success" | "failed" | "canceled" | "skipped" | "manual"
+        )
+    }
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ListJobsOption {
+    /// `created`, `pending`, `running`, `failed`, `success`, `canceled`, `skipped`,
+    /// `waiting_for_resource` or `manual`
+    pub scope: Option<String>,
+    /// Pipeline jobs only
+    pub include_retried: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct JobVariable {
+    pub key: String,
+    pub value: String,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct PlayJobOption {
+    pub job_variables_attributes: Option<Vec<JobVariable>>,
+}
@@ -1,13 +1,30 @@
+pub mod approval;
 pub mod auth;
+pub mod branch;
 pub mod builder;
 pub mod client;
 pub mod commit;
 pub mod config;
 pub mod error;
+pub mod file;
+mod header;
 pub mod hook;
+pub mod issue;
+pub mod job;
+pub mod merge_request;
+pub mod note;
+pub mod pagination;
+pub mod pipeline;
+pub mod ratelimit;
+pub mod release;
 pub mod repo;
+pub mod repository;
+pub mod retry;
 pub mod status;
+pub mod tag;
 pub mod user;
+#[cfg(feature = "webhook-server")]
+pub mod webhook_server;
 
 use chrono::{DateTime, Utc};
 pub use error::{Error, Result};
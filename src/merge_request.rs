@@ -0,0 +1,270 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+use crate::{commit::Diff, user::BasicUser};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Milestone {
+    pub id: i64,
+    pub iid: i64,
+    pub project_id: Option<i64>,
+    pub group_id: Option<i64>,
+    pub title: String,
+    pub description: Option<String>,
+    pub state: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub due_date: Option<NaiveDate>,
+    pub start_date: Option<NaiveDate>,
+    pub expired: Option<bool>,
+    pub web_url: Option<String>,
+}
+
+/// A label of a merge request or an issue, gitlab returns only the name unless
+/// `with_labels_details` is set
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum LabelRef {
+    Name(String),
+    Details(LabelDetails),
+}
+
+impl LabelRef {
+    pub fn name(&self) -> &str {
+        match self {
+            LabelRef::Name(name) => name,
+            LabelRef::Details(details) => &details.name,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LabelDetails {
+    pub id: i64,
+    pub name: String,
+    pub color: Option<String>,
+    pub text_color: Option<String>,
+    pub description: Option<String>,
+    pub description_html: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TimeStats {
+    pub time_estimate: i64,
+    pub total_time_spent: i64,
+    pub human_time_estimate: Option<String>,
+    pub human_total_time_spent: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct References {
+    pub short: String,
+    pub relative: String,
+    pub full: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TaskCompletionStatus {
+    pub count: i64,
+    pub completed_count: i64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DiffRefs {
+    pub base_sha: String,
+    pub head_sha: String,
+    pub start_sha: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MergeRequest {
+    pub id: i64,
+    pub iid: i64,
+    pub project_id: i64,
+    pub title: String,
+    pub description: Option<String>,
+    pub state: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub merged_by: Option<BasicUser>,
+    pub merged_at: Option<DateTime<Utc>>,
+    pub closed_by: Option<BasicUser>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub target_branch: String,
+    pub source_branch: String,
+    pub user_notes_count: i64,
+    pub upvotes: i64,
+    pub downvotes: i64,
+    pub author: BasicUser,
+    pub assignee: Option<BasicUser>,
+    #[serde(default)]
+    pub assignees: Vec<BasicUser>,
+    #[serde(default)]
+    pub reviewers: Vec<BasicUser>,
+    pub source_project_id: i64,
+    pub target_project_id: i64,
+    pub labels: Vec<LabelRef>,
+    #[serde(default)]
+    pub draft: bool,
+    pub work_in_progress: bool,
+    pub milestone: Option<Milestone>,
+    pub merge_when_pipeline_succeeds: bool,
+    pub merge_status: String,
+    pub detailed_merge_status: Option<String>,
+    pub sha: Option<String>,
+    pub merge_commit_sha: Option<String>,
+    pub squash_commit_sha: Option<String>,
+    pub discussion_locked: Option<bool>,
+    pub should_remove_source_branch: Option<bool>,
+    pub force_remove_source_branch: Option<bool>,
+    pub reference: Option<String>,
+    pub references: Option<References>,
+    pub web_url: String,
+    pub time_stats: Option<TimeStats>,
+    #[serde(default)]
+    pub squash: bool,
+    pub task_completion_status: Option<TaskCompletionStatus>,
+    #[serde(default)]
+    pub has_conflicts: bool,
+    #[serde(default)]
+    pub blocking_discussions_resolved: bool,
+    // only returned when getting a single merge request
+    pub diff_refs: Option<DiffRefs>,
+    pub changes_count: Option<String>,
+    pub rebase_in_progress: Option<bool>,
+    pub merge_error: Option<String>,
+}
+
+/// A merge request along with the diffs of its changed files
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MergeRequestChanges {
+    #[serde(flatten)]
+    pub merge_request: MergeRequest,
+    pub changes: Vec<Diff>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RebaseStatus {
+    pub rebase_in_progress: bool,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ListMergeRequestsOption {
+    /// `opened`, `closed`, `locked` or `merged`
+    pub state: Option<String>,
+    pub order_by: Option<String>,
+    pub sort: Option<String>,
+    pub milestone: Option<String>,
+    pub view: Option<String>,
+    /// Comma-separated label names
+    pub labels: Option<String>,
+    pub with_labels_details: Option<bool>,
+    pub with_merge_status_recheck: Option<bool>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    pub updated_after: Option<DateTime<Utc>>,
+    pub updated_before: Option<DateTime<Utc>>,
+    /// `created_by_me`, `assigned_to_me` or `all`
+    pub scope: Option<String>,
+    pub author_id: Option<i64>,
+    pub author_username: Option<String>,
+    pub assignee_id: Option<i64>,
+    pub reviewer_id: Option<i64>,
+    pub reviewer_username: Option<String>,
+    pub my_reaction_emoji: Option<String>,
+    pub source_branch: Option<String>,
+    pub target_branch: Option<String>,
+    pub search: Option<String>,
+    #[serde(rename = "in")]
+    pub in_: Option<String>,
+    /// `yes` or `no`
+    pub wip: Option<String>,
+    pub environment: Option<String>,
+    pub deployed_before: Option<DateTime<Utc>>,
+    pub deployed_after: Option<DateTime<Utc>>,
+    pub non_archived: Option<bool>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct CreateMergeRequestOption {
+    pub source_branch: String,
+    pub target_branch: String,
+    pub title: String,
+    pub assignee_id: Option<i64>,
+    pub assignee_ids: Option<Vec<i64>>,
+    pub reviewer_ids: Option<Vec<i64>>,
+    pub description: Option<String>,
+    pub target_project_id: Option<i64>,
+    /// Comma-separated label names
+    pub labels: Option<String>,
+    pub milestone_id: Option<i64>,
+    pub remove_source_branch: Option<bool>,
+    pub allow_collaboration: Option<bool>,
+    pub squash: Option<bool>,
+}
+
+impl CreateMergeRequestOption {
+    pub fn new(source_branch: &str, target_branch: &str, title: &str) -> Self {
+        Self {
+            source_branch: source_branch.to_string(),
+            target_branch: target_branch.to_string(),
+            title: title.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct UpdateMergeRequestOption {
+    pub target_branch: Option<String>,
+    pub title: Option<String>,
+    pub assignee_id: Option<i64>,
+    pub assignee_ids: Option<Vec<i64>>,
+    pub reviewer_ids: Option<Vec<i64>>,
+    pub milestone_id: Option<i64>,
+    /// Comma-separated label names
+    pub labels: Option<String>,
+    pub add_labels: Option<String>,
+    pub remove_labels: Option<String>,
+    pub description: Option<String>,
+    /// `close` or `reopen`
+    pub state_event: Option<String>,
+    pub remove_source_branch: Option<bool>,
+    pub squash: Option<bool>,
+    pub discussion_locked: Option<bool>,
+    pub allow_collaboration: Option<bool>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct AcceptMergeRequestOption {
+    pub merge_commit_message: Option<String>,
+    pub squash_commit_message: Option<String>,
+    pub squash: Option<bool>,
+    pub should_remove_source_branch: Option<bool>,
+    pub merge_when_pipeline_succeeds: Option<bool>,
+    /// Merge only if the head of the source branch is still this sha
+    pub sha: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn label_ref_accepts_names_and_details() {
+        let labels: Vec<LabelRef> = serde_json::from_str(
+            r##"["bug", {"id": 1, "name": "feature", "color": "#428BCA",
+                "text_color": "#FFFFFF", "description": null,
+                "description_html": ""}]"##,
+        )
+        .unwrap();
+        let names: Vec<_> = labels.iter().map(LabelRef::name).collect();
+        assert_eq!(names, ["bug", "feature"]);
+        assert!(matches!(&labels[1], LabelRef::Details(details) if details.id == 1));
+    }
+}
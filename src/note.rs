@@ -0,0 +1,181 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+use crate::user::BasicUser;
+
+/// The resource that notes and discussions are attached to
+#[derive(Debug, Clone, Copy)]
+pub enum Noteable<'a> {
+    Issue(i64),
+    MergeRequest(i64),
+    Snippet(i64),
+    /// Only supports discussions, not notes
+    Commit(&'a str),
+}
+
+impl Noteable<'_> {
+    /// The path of the resource relative to the project, e.g. `merge_requests/1`
+    pub fn path(&self) -> String {
+        match self {
+            Noteable::Issue(iid) => format!("issues/{}", iid),
+            Noteable::MergeRequest(iid) => format!("merge_requests/{}", iid),
+            Noteable::Snippet(id) => format!("snippets/{}", id),
+            Noteable::Commit(sha) => format!("repository/commits/{}", sha),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LinePosition {
+    pub line_code: Option<String>,
+    /// `old` or `new`
+    #[serde(rename = "type")]
+    pub type_: Option<String>,
+    pub old_line: Option<i64>,
+    pub new_line: Option<i64>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LineRange {
+    pub start: LinePosition,
+    pub end: LinePosition,
+}
+
+/// The position of an inline comment on a diff.
+///
+/// The shas come from the `diff_refs` of the merge request. Set `new_line` for added
+/// lines, `old_line` for removed lines and both for unchanged lines.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Position {
+    pub base_sha: String,
+    pub start_sha: String,
+    pub head_sha: String,
+    /// `text` or `image`
+    pub position_type: String,
+    pub old_path: Option<String>,
+    pub new_path: Option<String>,
+    pub old_line: Option<i64>,
+    pub new_line: Option<i64>,
+    pub line_range: Option<LineRange>,
+}
+
+impl Position {
+    /// Position of a text diff line
+    pub fn text(
+        base_sha: &str,
+        start_sha: &str,
+        head_sha: &str,
+        old_path: &str,
+        new_path: &str,
+    ) -> Self {
+        Self {
+            base_sha: base_sha.to_string(),
+            start_sha: start_sha.to_string(),
+            head_sha: head_sha.to_string(),
+            position_type: "text".to_string(),
+            old_path: Some(old_path.to_string()),
+            new_path: Some(new_path.to_string()),
+            old_line: None,
+            new_line: None,
+            line_range: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Note {
+    pub id: i64,
+    /// `DiffNote`, `DiscussionNote` or `None` for plain notes
+    #[serde(rename = "type")]
+    pub type_: Option<String>,
+    pub body: String,
+    pub attachment: Option<String>,
+    pub author: BasicUser,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub system: bool,
+    pub noteable_id: Option<i64>,
+    pub noteable_type: String,
+    pub noteable_iid: Option<i64>,
+    pub commit_id: Option<String>,
+    pub position: Option<Position>,
+    #[serde(default)]
+    pub resolvable: bool,
+    pub resolved: Option<bool>,
+    pub resolved_by: Option<BasicUser>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub confidential: Option<bool>,
+    pub internal: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Discussion {
+    pub id: String,
+    pub individual_note: bool,
+    pub notes: Vec<Note>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ListNotesOption {
+    /// `created_at` or `updated_at`
+    pub order_by: Option<String>,
+    /// `asc` or `desc`
+    pub sort: Option<String>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct CreateNoteOption {
+    pub body: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub internal: Option<bool>,
+}
+
+impl CreateNoteOption {
+    pub fn new(body: &str) -> Self {
+        Self {
+            body: body.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct UpdateNoteOption {
+    pub body: Option<String>,
+    /// Resolve or unresolve the note, only for resolvable notes
+    pub resolved: Option<bool>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct CreateDiscussionOption {
+    pub body: String,
+    /// Start an inline review thread on the diff
+    pub position: Option<Position>,
+    /// Sha of the commit to start the discussion on (merge requests only)
+    pub commit_id: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl CreateDiscussionOption {
+    pub fn new(body: &str) -> Self {
+        Self {
+            body: body.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Inline comment on the diff
+    pub fn inline(body: &str, position: Position) -> Self {
+        Self {
+            body: body.to_string(),
+            position: Some(position),
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,324 @@
+use std::marker::PhantomData;
+
+use futures::{stream, Stream, TryStreamExt};
+use http::Method;
+use reqwest::header::HeaderMap;
+use serde::{de::DeserializeOwned, Serialize};
+use url::Url;
+
+use crate::{client::Gritlab, header::header_num, Error, Result};
+
+/// Pagination information returned by gitlab in the response headers
+#[derive(Debug, Clone, Default)]
+pub struct PageInfo {
+    pub page: Option<u32>,
+    pub per_page: Option<u32>,
+    pub next_page: Option<u32>,
+    pub prev_page: Option<u32>,
+    pub total: Option<u64>,
+    pub total_pages: Option<u32>,
+    pub next_url: Option<Url>,
+}
+
+impl PageInfo {
+    /// Parse the `Link`, `X-Page`, `X-Next-Page`, `X-Total`... headers
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        Self {
+            page: header_num(headers, "x-page"),
+            per_page: header_num(headers, "x-per-page"),
+            next_page: header_num(headers, "x-next-page"),
+            prev_page: header_num(headers, "x-prev-page"),
+            total: header_num(headers, "x-total"),
+            total_pages: header_num(headers, "x-total-pages"),
+            next_url: headers
+                .get_all("link")
+                .iter()
+                .filter_map(|v| v.to_str().ok())
+                .find_map(parse_link_next),
+        }
+    }
+
+    /// Whether there are more pages after this one
+    pub fn has_next(&self) -> bool {
+        self.next_url.is_some() || self.next_page.is_some()
+    }
+}
+
+/// A single page of a list api
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub info: PageInfo,
+}
+
+/// Lazy page-by-page iterator over a list api.
+///
+/// The next page is located by the `Link: <...>; rel="next"` header, falling back to
+/// `X-Next-Page` when gitlab doesn't send the link or when the link points to
+/// another host than the configured one. A link to another host without
+/// `X-Next-Page` fails the iteration instead of silently ending it.
+#[derive(Debug, Clone)]
+pub struct Pages<T> {
+    cli: Gritlab,
+    next: Option<Url>,
+    err_mes: String,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Pages<T>
+where
+    T: DeserializeOwned,
+{
+    pub(crate) fn new(cli: Gritlab, url: Url, err_mes: impl Into<String>) -> Self {
+        Self {
+            cli,
+            next: Some(url),
+            err_mes: err_mes.into(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Set the number of items per page (gitlab defaults to 20, max 100)
+    pub fn per_page(mut self, per_page: u32) -> Self {
+        if let Some(url) = self.next.as_mut() {
+            set_query_param(url, "per_page", &per_page.to_string());
+        }
+        self
+    }
+
+    /// Start from the specified page instead of the first one
+    pub fn page(mut self, page: u32) -> Self {
+        if let Some(url) = self.next.as_mut() {
+            set_query_param(url, "page", &page.to_string());
+        }
+        self
+    }
+
+    /// Fetch the next page, returns `None` when all pages have been fetched
+    pub async fn next_page(&mut self) -> Result<Option<Page<T>>> {
+        let url = match self.next.take() {
+            Some(url) => url,
+            None => return Ok(None),
+        };
+
+        let req = self.cli.request_url(Method::GET, url.clone())?;
+        let resp = self
+            .cli
+            .send(req)
+            .await
+            .map_err(|e| e.context(&self.err_mes))?;
+        let info = PageInfo::from_headers(resp.headers());
+        let items: Vec<T> = resp.json().await?;
+
+        self.next = next_url(url, &info, &self.cli.abs_url("")?)
+            .map_err(|e| Error::Other(format!("{}: {}", self.err_mes, e)))?;
+        Ok(Some(Page { items, info }))
+    }
+
+    /// Fetch all the remaining pages and collect the items
+    pub async fn collect_all(mut self) -> Result<Vec<T>> {
+        let mut items = Vec::new();
+        while let Some(page) = self.next_page().await? {
+            items.extend(page.items);
+        }
+        Ok(items)
+    }
+
+    /// Turn into a stream of items, the next page is fetched only when the items of
+    /// the current page have been consumed
+    pub fn into_stream(self) -> impl Stream<Item = Result<T>> {
+        stream::try_unfold(self, |mut pages| async move {
+            let page = match pages.next_page().await? {
+                Some(page) => page,
+                None => return Result::Ok(None),
+            };
+            let items = stream::iter(page.items.into_iter().map(Result::Ok));
+            Ok(Some((items, pages)))
+        })
+        .try_flatten()
+    }
+}
+
+/// Build the url of a list api with `query` serialized into the query string
+pub(crate) fn list_url<Q>(mut url: Url, query: &Q) -> Result<Url>
+where
+    Q: Serialize + ?Sized,
+{
+    {
+        let mut pairs = url.query_pairs_mut();
+        query.serialize(serde_urlencoded::Serializer::new(&mut pairs))?;
+    }
+    if url.query() == Some("") {
+        url.set_query(None);
+    }
+    Ok(url)
+}
+
+fn set_query_param(url: &mut Url, key: &str, value: &str) {
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(k, _)| k != key)
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    url.query_pairs_mut()
+        .clear()
+        .extend_pairs(pairs)
+        .append_pair(key, value);
+}
+
+/// The url of the page after `url`, never sending the credentials to another host
+/// than the configured one
+fn next_url(url: Url, info: &PageInfo, base_url: &Url) -> Result<Option<Url>> {
+    let link = info.next_url.as_ref();
+    match (link, info.next_page) {
+        (Some(link), _) if same_origin(link, base_url) => Ok(Some(link.clone())),
+        (_, Some(next_page)) => {
+            let mut next_url = url;
+            set_query_param(&mut next_url, "page", &next_page.to_string());
+            Ok(Some(next_url))
+        }
+        (Some(link), None) => Err(Error::Other(format!(
+            "next page link {} doesn't point to {}",
+            link, base_url
+        ))),
+        (None, None) => Ok(None),
+    }
+}
+
+fn same_origin(url: &Url, base_url: &Url) -> bool {
+    url.scheme() == base_url.scheme()
+        && url.host_str() == base_url.host_str()
+        && url.port_or_known_default() == base_url.port_or_known_default()
+}
+
+/// Parse the `rel="next"` target of a `Link` header
+fn parse_link_next(link: &str) -> Option<Url> {
+    link.split(',').find_map(|part| {
+        let mut segs = part.split(';');
+        let target = segs.next()?.trim().strip_prefix('<')?.strip_suffix('>')?;
+        let is_next = segs.any(|param| {
+            let param = param.trim().replace(' ', "");
+            param == "rel=\"next\"" || param == "rel=next"
+        });
+        if is_next {
+            Url::parse(target).ok()
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+
+    use super::*;
+
+    #[test]
+    fn parse_link_next_finds_next() {
+        let link = r#"<https://gitlab.example.com/api/v4/projects?page=1>; rel="prev", <https://gitlab.example.com/api/v4/projects?page=3>; rel="next", <https://gitlab.example.com/api/v4/projects?page=9>; rel="last""#;
+        assert_eq!(
+            parse_link_next(link).unwrap().as_str(),
+            "https://gitlab.example.com/api/v4/projects?page=3"
+        );
+    }
+
+    #[test]
+    fn parse_link_next_unquoted_rel() {
+        let link = "<https://gitlab.example.com/api/v4/projects?id_after=42>; rel=next";
+        assert_eq!(
+            parse_link_next(link).unwrap().as_str(),
+            "https://gitlab.example.com/api/v4/projects?id_after=42"
+        );
+    }
+
+    #[test]
+    fn parse_link_next_without_next() {
+        let link =
+            r#"<https://gitlab.example.com/api/v4/projects?page=1>; rel="first""#;
+        assert!(parse_link_next(link).is_none());
+        assert!(parse_link_next("").is_none());
+        assert!(parse_link_next("garbage; rel=\"next\"").is_none());
+    }
+
+    #[derive(Serialize)]
+    struct Query {
+        search: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        archived: Option<bool>,
+    }
+
+    #[test]
+    fn list_url_serializes_query() {
+        let base = Url::parse("https://gitlab.example.com/api/v4/projects").unwrap();
+        let query = Query {
+            search: Some("a b&c".to_string()),
+            archived: Some(false),
+        };
+        assert_eq!(
+            list_url(base, &query).unwrap().as_str(),
+            "https://gitlab.example.com/api/v4/projects?search=a+b%26c&archived=false"
+        );
+    }
+
+    #[test]
+    fn list_url_without_query() {
+        let base = Url::parse("https://gitlab.example.com/api/v4/projects").unwrap();
+        assert_eq!(list_url(base.clone(), &()).unwrap().as_str(), base.as_str());
+        let none: Option<Query> = None;
+        assert_eq!(
+            list_url(base.clone(), &none).unwrap().as_str(),
+            base.as_str()
+        );
+    }
+
+    #[test]
+    fn same_origin_checks_scheme_host_and_port() {
+        let base = Url::parse("https://gitlab.example.com/").unwrap();
+        let same = |url: &str| same_origin(&Url::parse(url).unwrap(), &base);
+        assert!(same("https://gitlab.example.com/api/v4/projects?page=2"));
+        assert!(same("https://gitlab.example.com:443/api/v4/projects"));
+        assert!(!same("http://gitlab.example.com/api/v4/projects"));
+        assert!(!same("https://evil.example.com/api/v4/projects"));
+        assert!(!same("https://gitlab.example.com:8443/api/v4/projects"));
+    }
+
+    #[test]
+    fn next_url_follows_same_origin_links_only() {
+        let base = Url::parse("https://gitlab.example.com/").unwrap();
+        let url =
+            Url::parse("https://gitlab.example.com/api/v4/projects?page=1").unwrap();
+        let info = |next_url: &str, next_page| PageInfo {
+            next_url: Url::parse(next_url).ok(),
+            next_page,
+            ..Default::default()
+        };
+        let next =
+            |info| next_url(url.clone(), &info, &base).map(|url| url.map(String::from));
+
+        assert_eq!(
+            next(info(
+                "https://gitlab.example.com/api/v4/projects?id_after=9",
+                None
+            ))
+            .unwrap()
+            .as_deref(),
+            Some("https://gitlab.example.com/api/v4/projects?id_after=9")
+        );
+        assert_eq!(
+            next(info(
+                "https://evil.example.com/api/v4/projects?page=2",
+                Some(2)
+            ))
+            .unwrap()
+            .as_deref(),
+            Some("https://gitlab.example.com/api/v4/projects?page=2")
+        );
+        assert!(next(info(
+            "https://evil.example.com/api/v4/projects?id_after=9",
+            None
+        ))
+        .is_err());
+        assert_eq!(next(info("", None)).unwrap(), None);
+    }
+}
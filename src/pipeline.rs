@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+use crate::user::BasicUser;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DetailedStatus {
+    pub icon: String,
+    pub text: String,
+    pub label: String,
+    pub group: String,
+    pub tooltip: Option<String>,
+    pub has_details: bool,
+    pub details_path: Option<String>,
+    pub favicon: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Pipeline {
+    pub id: i64,
+    pub iid: Option<i64>,
+    pub project_id: i64,
+    pub sha: String,
+    #[serde(rename = "ref")]
+    pub ref_: String,
+    pub status: String,
+    pub source: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub web_url: String,
+    // only returned when getting a single pipeline
+    pub before_sha: Option<String>,
+    pub tag: Option<bool>,
+    pub yaml_errors: Option<String>,
+    pub user: Option<BasicUser>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub committed_at: Option<DateTime<Utc>>,
+    pub duration: Option<i64>,
+    pub queued_duration: Option<f64>,
+    pub coverage: Option<String>,
+    pub detailed_status: Option<DetailedStatus>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PipelineVariable {
+    pub key: String,
+    pub value: String,
+    /// `env_var` or `file`
+    pub variable_type: Option<String>,
+}
+
+impl PipelineVariable {
+    pub fn new(key: &str, value: &str) -> Self {
+        Self {
+            key: key.to_string(),
+            value: value.to_string(),
+            variable_type: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TestReportTotal {
+    pub time: f64,
+    pub count: i64,
+    pub success: i64,
+    pub failed: i64,
+    pub skipped: i64,
+    pub error: i64,
+    pub suite_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TestSuiteSummary {
+    pub name: String,
+    pub total_time: f64,
+    pub total_count: i64,
+    pub success_count: i64,
+    pub failed_count: i64,
+    pub skipped_count: i64,
+    pub error_count: i64,
+    pub build_ids: Vec<i64>,
+    pub suite_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TestReportSummary {
+    pub total: TestReportTotal,
+    pub test_suites: Vec<TestSuiteSummary>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ListPipelinesOption {
+    /// `running`, `pending`, `finished`, `branches` or `tags`
+    pub scope: Option<String>,
+    pub status: Option<String>,
+    /// e.g. `push`, `web`, `trigger`, `schedule`, `api` or `merge_request_event`
+    pub source: Option<String>,
+    #[serde(rename = "ref")]
+    pub ref_: Option<String>,
+    pub sha: Option<String>,
+    pub yaml_errors: Option<bool>,
+    pub username: Option<String>,
+    pub name: Option<String>,
+    pub updated_after: Option<DateTime<Utc>>,
+    pub updated_before: Option<DateTime<Utc>>,
+    pub order_by: Option<String>,
+    pub sort: Option<String>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct CreatePipelineOption {
+    #[serde(rename = "ref")]
+    pub ref_: String,
+    pub variables: Option<Vec<PipelineVariable>>,
+}
+
+impl CreatePipelineOption {
+    pub fn new(ref_: &str) -> Self {
+        Self {
+            ref_: ref_.to_string(),
+            variables: None,
+        }
+    }
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct TriggerPipelineOption {
+    /// The pipeline trigger token
+    pub token: String,
+    #[serde(rename = "ref")]
+    pub ref_: String,
+    pub variables: Option<HashMap<String, String>>,
+}
+
+impl TriggerPipelineOption {
+    pub fn new(token: &str, ref_: &str) -> Self {
+        Self {
+            token: token.to_string(),
+            ref_: ref_.to_string(),
+            variables: None,
+        }
+    }
+}
@@ -0,0 +1,228 @@
+use std::{sync::Mutex, time::Duration};
+
+use chrono::{DateTime, TimeZone, Utc};
+use reqwest::header::HeaderMap;
+use tokio::time::sleep;
+
+use crate::header::header_num;
+
+/// Policy to pace the outgoing requests by the `RateLimit-*` headers of gitlab
+#[derive(Debug, Clone)]
+pub struct RateLimitPolicy {
+    /// Delay the requests when the observed budget is exhausted
+    pub enabled: bool,
+    /// Number of requests kept in reserve, i.e. start waiting for the reset when
+    /// `RateLimit-Remaining` drops to this value
+    pub reserve: u64,
+    /// Upper bound of the wait for the reset, the request is sent anyway afterwards.
+    /// Guards against far-future resets, e.g. of daily throttles or skewed clocks.
+    pub max_wait: Duration,
+}
+
+impl Default for RateLimitPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            reserve: 0,
+            max_wait: Duration::from_secs(60),
+        }
+    }
+}
+
+/// The rate limit observed from the latest response
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitStatus {
+    /// `RateLimit-Limit`
+    pub limit: Option<u64>,
+    /// `RateLimit-Remaining`, decremented locally for every request sent since
+    pub remaining: u64,
+    /// `RateLimit-Reset`
+    pub reset_at: DateTime<Utc>,
+    /// When the headers were received
+    pub observed_at: DateTime<Utc>,
+}
+
+/// Rate limiter shared by all the clones of a [`Gritlab`](crate::client::Gritlab)
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    status: Mutex<Option<RateLimitStatus>>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The latest observed rate limit
+    pub fn status(&self) -> Option<RateLimitStatus> {
+        *self.status.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Wait until the request is allowed by the observed budget, and take one request
+    /// out of the budget. The wait is bounded by `policy.max_wait`.
+    pub async fn acquire(&self, policy: &RateLimitPolicy) {
+        if !policy.enabled {
+            return;
+        }
+
+        let deadline = tokio::time::Instant::now() + policy.max_wait;
+        loop {
+            let wait = {
+                let mut status = self.status.lock().unwrap_or_else(|e| e.into_inner());
+                match status.as_mut() {
+                    Some(status) if status.reset_at > Utc::now() => {
+                        if status.remaining > policy.reserve {
+                            status.remaining -= 1;
+                            return;
+                        }
+                        (status.reset_at - Utc::now())
+                            .to_std()
+                            .unwrap_or(Duration::ZERO)
+                    }
+                    // nothing observed yet or the window has been reset
+                    _ => return,
+                }
+            };
+
+            let left = deadline.saturating_duration_since(tokio::time::Instant::now());
+            if left.is_zero() {
+                return;
+            }
+            sleep(wait.min(left)).await;
+        }
+    }
+
+    /// Update the budget from the `RateLimit-*` headers of a response
+    pub fn observe(&self, headers: &HeaderMap) {
+        let remaining = match header_num::<u64>(headers, "ratelimit-remaining") {
+            Some(remaining) => remaining,
+            None => return,
+        };
+        let reset_at = match header_num::<i64>(headers, "ratelimit-reset")
+            .and_then(|reset| Utc.timestamp_opt(reset, 0).single())
+        {
+            Some(reset_at) => reset_at,
+            None => return,
+        };
+
+        let mut status = self.status.lock().unwrap_or_else(|e| e.into_inner());
+        // responses of concurrent requests may arrive out of order, keep the lowest
+        // budget seen within the same window and ignore the late previous windows
+        let remaining = match *status {
+            Some(prev) if prev.reset_at > reset_at => return,
+            Some(prev) if prev.reset_at == reset_at => prev.remaining.min(remaining),
+            _ => remaining,
+        };
+        *status = Some(RateLimitStatus {
+            limit: header_num(headers, "ratelimit-limit"),
+            remaining,
+            reset_at,
+            observed_at: Utc::now(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use chrono::Duration as ChronoDuration;
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    fn headers(remaining: u64, reset_at: DateTime<Utc>) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("ratelimit-limit", HeaderValue::from_static("2000"));
+        headers.insert("ratelimit-remaining", HeaderValue::from(remaining));
+        headers.insert("ratelimit-reset", HeaderValue::from(reset_at.timestamp()));
+        headers
+    }
+
+    fn limiter(remaining: u64, reset_in: ChronoDuration) -> RateLimiter {
+        let limiter = RateLimiter::new();
+        *limiter.status.lock().unwrap() = Some(RateLimitStatus {
+            limit: None,
+            remaining,
+            reset_at: Utc::now() + reset_in,
+            observed_at: Utc::now(),
+        });
+        limiter
+    }
+
+    #[test]
+    fn observe_keeps_the_lowest_remaining_of_a_window() {
+        let limiter = RateLimiter::new();
+        let reset_at = Utc.timestamp(Utc::now().timestamp() + 60, 0);
+        limiter.observe(&headers(10, reset_at));
+        limiter.observe(&headers(12, reset_at));
+        let status = limiter.status().unwrap();
+        assert_eq!(status.remaining, 10);
+        assert_eq!(status.limit, Some(2000));
+        assert_eq!(status.reset_at, reset_at);
+
+        limiter.observe(&headers(8, reset_at));
+        assert_eq!(limiter.status().unwrap().remaining, 8);
+    }
+
+    #[test]
+    fn observe_replaces_the_previous_window() {
+        let limiter = RateLimiter::new();
+        let reset_at = Utc.timestamp(Utc::now().timestamp() + 60, 0);
+        limiter.observe(&headers(1, reset_at));
+
+        let next_reset_at = reset_at + ChronoDuration::seconds(60);
+        limiter.observe(&headers(1999, next_reset_at));
+        let status = limiter.status().unwrap();
+        assert_eq!((status.remaining, status.reset_at), (1999, next_reset_at));
+
+        // a late response of the previous window doesn't restore it
+        limiter.observe(&headers(0, reset_at));
+        let status = limiter.status().unwrap();
+        assert_eq!((status.remaining, status.reset_at), (1999, next_reset_at));
+    }
+
+    #[test]
+    fn observe_ignores_incomplete_headers() {
+        let limiter = RateLimiter::new();
+        let mut headers = HeaderMap::new();
+        headers.insert("ratelimit-remaining", HeaderValue::from_static("5"));
+        limiter.observe(&headers);
+        assert!(limiter.status().is_none());
+    }
+
+    #[tokio::test]
+    async fn acquire_takes_from_the_budget_above_the_reserve() {
+        let limiter = limiter(2, ChronoDuration::hours(1));
+        let started = Instant::now();
+        limiter.acquire(&RateLimitPolicy::default()).await;
+        assert!(started.elapsed() < Duration::from_millis(100));
+        assert_eq!(limiter.status().unwrap().remaining, 1);
+    }
+
+    #[tokio::test]
+    async fn acquire_waits_for_the_reset_below_the_reserve() {
+        let limiter = limiter(1, ChronoDuration::milliseconds(300));
+        let policy = RateLimitPolicy {
+            reserve: 1,
+            ..Default::default()
+        };
+        let started = Instant::now();
+        limiter.acquire(&policy).await;
+        assert!(started.elapsed() >= Duration::from_millis(250));
+    }
+
+    #[tokio::test]
+    async fn acquire_wait_is_capped() {
+        let limiter = limiter(0, ChronoDuration::days(1));
+        let policy = RateLimitPolicy {
+            max_wait: Duration::from_millis(200),
+            ..Default::default()
+        };
+        let started = Instant::now();
+        limiter.acquire(&policy).await;
+        let elapsed = started.elapsed();
+        assert!(elapsed >= Duration::from_millis(200));
+        assert!(elapsed < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,136 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+use crate::{commit::Commit, merge_request::Milestone, user::BasicUser};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ReleaseSource {
+    pub format: String,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ReleaseLink {
+    pub id: i64,
+    pub name: String,
+    pub url: String,
+    pub direct_asset_url: Option<String>,
+    /// `other`, `runbook`, `image` or `package`
+    pub link_type: Option<String>,
+    pub external: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ReleaseAssets {
+    pub count: i64,
+    #[serde(default)]
+    pub sources: Vec<ReleaseSource>,
+    #[serde(default)]
+    pub links: Vec<ReleaseLink>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ReleaseEvidence {
+    pub sha: String,
+    pub filepath: String,
+    pub collected_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Release {
+    pub tag_name: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub released_at: Option<DateTime<Utc>>,
+    pub upcoming_release: Option<bool>,
+    pub author: Option<BasicUser>,
+    pub commit: Option<Commit>,
+    #[serde(default)]
+    pub milestones: Vec<Milestone>,
+    pub commit_path: Option<String>,
+    pub tag_path: Option<String>,
+    pub assets: ReleaseAssets,
+    #[serde(default)]
+    pub evidences: Vec<ReleaseEvidence>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ListReleasesOption {
+    /// `released_at` or `created_at`
+    pub order_by: Option<String>,
+    pub sort: Option<String>,
+    pub include_html_description: Option<bool>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct CreateReleaseLinkOption {
+    pub name: String,
+    pub url: String,
+    /// Path for a permanent link to the asset, e.g. `/binaries/linux-amd64`
+    pub direct_asset_path: Option<String>,
+    /// `other`, `runbook`, `image` or `package`
+    pub link_type: Option<String>,
+}
+
+impl CreateReleaseLinkOption {
+    pub fn new(name: &str, url: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            url: url.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct UpdateReleaseLinkOption {
+    pub name: Option<String>,
+    pub url: Option<String>,
+    pub direct_asset_path: Option<String>,
+    pub link_type: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct CreateReleaseAssets {
+    pub links: Vec<CreateReleaseLinkOption>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct CreateReleaseOption {
+    pub tag_name: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// Create the tag from this ref if it doesn't exist
+    #[serde(rename = "ref")]
+    pub ref_: Option<String>,
+    pub tag_message: Option<String>,
+    /// Milestone titles
+    pub milestones: Option<Vec<String>>,
+    pub assets: Option<CreateReleaseAssets>,
+    pub released_at: Option<DateTime<Utc>>,
+}
+
+impl CreateReleaseOption {
+    pub fn new(tag_name: &str) -> Self {
+        Self {
+            tag_name: tag_name.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct UpdateReleaseOption {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// Milestone titles, an empty list removes all milestones
+    pub milestones: Option<Vec<String>>,
+    pub released_at: Option<DateTime<Utc>>,
+}
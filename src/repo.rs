@@ -90,6 +90,11 @@ pub struct ListProjectsOption {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub owned: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub pagination: Option<String>,
+    /// Items per page, gitlab defaults to 20 and allows at most 100
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_page: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub repository_checksum_failed: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub repository_storage: Option<String>,
@@ -0,0 +1,140 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+use crate::commit::{Commit, Diff};
+
+/// A file or directory of the repository tree
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TreeNode {
+    pub id: String,
+    pub name: String,
+    /// `blob`, `tree` or `commit` (submodule)
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub path: String,
+    pub mode: String,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ListTreeOption {
+    /// Directory to list, defaults to the root
+    pub path: Option<String>,
+    #[serde(rename = "ref")]
+    pub ref_: Option<String>,
+    pub recursive: Option<bool>,
+}
+
+/// The result of comparing two refs
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Compare {
+    /// The head commit of `to`, `None` if both refs are the same
+    pub commit: Option<Commit>,
+    pub commits: Vec<Commit>,
+    pub diffs: Vec<Diff>,
+    /// Whether gitlab gave up computing the diffs
+    #[serde(default)]
+    pub compare_timeout: bool,
+    #[serde(default)]
+    pub compare_same_ref: bool,
+    pub web_url: Option<String>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct CompareOption {
+    pub from: String,
+    pub to: String,
+    /// The project to compare from, defaults to this one
+    pub from_project_id: Option<i64>,
+    /// Compare `from..to` instead of `from...to`
+    pub straight: Option<bool>,
+    pub unidiff: Option<bool>,
+}
+
+impl CompareOption {
+    pub fn new(from: &str, to: &str) -> Self {
+        Self {
+            from: from.to_string(),
+            to: to.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ArchiveOption {
+    /// Commit, branch or tag to archive, defaults to the default branch
+    pub sha: Option<String>,
+    /// Only archive this subpath
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Contributor {
+    pub name: String,
+    pub email: String,
+    pub commits: i64,
+    pub additions: i64,
+    pub deletions: i64,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ListContributorsOption {
+    /// `name`, `email` or `commits`
+    pub order_by: Option<String>,
+    pub sort: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ChangelogNotes {
+    pub notes: String,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct GenerateChangelogOption {
+    /// Semantic version of the release
+    pub version: String,
+    /// Start of the range of commits, defaults to the tag of the previous version
+    pub from: Option<String>,
+    /// End of the range of commits, defaults to the default branch
+    pub to: Option<String>,
+    pub date: Option<DateTime<Utc>>,
+    /// Git trailer used to include commits, defaults to `Changelog`
+    pub trailer: Option<String>,
+    pub config_file: Option<String>,
+}
+
+impl GenerateChangelogOption {
+    pub fn new(version: &str) -> Self {
+        Self {
+            version: version.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct CommitChangelogOption {
+    #[serde(flatten)]
+    pub changelog: GenerateChangelogOption,
+    /// Branch to commit to, defaults to the default branch
+    pub branch: Option<String>,
+    /// Changelog file, defaults to `CHANGELOG.md`
+    pub file: Option<String>,
+    pub message: Option<String>,
+}
+
+impl CommitChangelogOption {
+    pub fn new(version: &str) -> Self {
+        Self {
+            changelog: GenerateChangelogOption::new(version),
+            ..Default::default()
+        }
+    }
+}
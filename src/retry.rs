@@ -0,0 +1,233 @@
+use std::time::Duration;
+
+use chrono::{DateTime, TimeZone, Utc};
+use http::{Method, StatusCode};
+use rand::Rng;
+use reqwest::header::HeaderMap;
+
+use crate::header::{header_num, header_str};
+
+/// Policy to retry the requests failed with transient errors, i.e. `429 Too Many
+/// Requests`, `502`/`503`/`504` and connection errors
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Max attempts including the first one, `1` disables retrying
+    pub max_attempts: u32,
+    /// Backoff before the first retry, doubled for every following retry
+    pub initial_backoff: Duration,
+    /// Upper bound of the exponential backoff
+    pub max_backoff: Duration,
+    /// Randomize the backoff to avoid retrying in lockstep with other clients
+    pub jitter: bool,
+    /// Upper bound of the delay requested by gitlab through `Retry-After` or
+    /// `RateLimit-Reset`
+    pub max_server_delay: Duration,
+    /// Also retry the methods which aren't safe to repeat, i.e. everything but GET,
+    /// HEAD and OPTIONS. See [`Gritlab::retry_non_idempotent`] to opt in per call.
+    ///
+    /// [`Gritlab::retry_non_idempotent`]: crate::client::Gritlab::retry_non_idempotent
+    pub retry_non_idempotent: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            jitter: true,
+            max_server_delay: Duration::from_secs(60),
+            retry_non_idempotent: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries
+    pub fn never() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    pub fn max_server_delay(mut self, max: Duration) -> Self {
+        self.max_server_delay = max;
+        self
+    }
+
+    pub fn retry_non_idempotent(mut self, retry: bool) -> Self {
+        self.retry_non_idempotent = retry;
+        self
+    }
+
+    /// Whether requests of the method may be retried. PUT and DELETE aren't retried
+    /// by default: gitlab may have applied a request whose response was lost, e.g. a
+    /// retried `update_file` would then fail on its stale `last_commit_id`.
+    pub fn should_retry_method(&self, method: &Method) -> bool {
+        self.retry_non_idempotent
+            || matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+    }
+
+    /// Whether the response status is considered transient
+    pub fn should_retry_status(&self, status: StatusCode) -> bool {
+        matches!(
+            status,
+            StatusCode::TOO_MANY_REQUESTS
+                | StatusCode::BAD_GATEWAY
+                | StatusCode::SERVICE_UNAVAILABLE
+                | StatusCode::GATEWAY_TIMEOUT
+        )
+    }
+
+    /// The delay before the `attempt`-th retry (starting from 1), `resp` is the
+    /// status and headers of the failed response.
+    ///
+    /// `Retry-After` of the failed response takes precedence over the exponential
+    /// backoff, as well as `RateLimit-Reset` when the response is rate limited, bounded
+    /// by `max_server_delay`.
+    pub fn delay(
+        &self,
+        attempt: u32,
+        resp: Option<(StatusCode, &HeaderMap)>,
+    ) -> Duration {
+        if let Some(delay) =
+            resp.and_then(|(status, headers)| server_delay(status, headers))
+        {
+            return delay.min(self.max_server_delay);
+        }
+
+        let exp = self
+            .initial_backoff
+            .checked_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff);
+        if self.jitter {
+            // equal jitter: keep half of the backoff, randomize the other half
+            let half = exp / 2;
+            half + rand::thread_rng().gen_range(Duration::ZERO..=half)
+        } else {
+            exp
+        }
+    }
+}
+
+/// The delay requested by gitlab through `Retry-After`, or `RateLimit-Reset` if the
+/// request was rate limited. gitlab sends `RateLimit-Reset` with every response, it
+/// doesn't tell when to retry a `5xx`.
+fn server_delay(status: StatusCode, headers: &HeaderMap) -> Option<Duration> {
+    if let Some(retry_after) = header_str(headers, "retry-after") {
+        if let Ok(secs) = retry_after.parse::<u64>() {
+            return Some(Duration::from_secs(secs));
+        }
+        if let Ok(at) = DateTime::parse_from_rfc2822(retry_after) {
+            return Some(until(at.with_timezone(&Utc)));
+        }
+    }
+
+    let rate_limited = status == StatusCode::TOO_MANY_REQUESTS
+        || header_num::<u64>(headers, "ratelimit-remaining") == Some(0);
+    if !rate_limited {
+        return None;
+    }
+    let reset = header_str(headers, "ratelimit-reset")?
+        .parse::<i64>()
+        .ok()?;
+    let at = Utc.timestamp_opt(reset, 0).single()?;
+    Some(until(at))
+}
+
+fn until(at: DateTime<Utc>) -> Duration {
+    (at - Utc::now()).to_std().unwrap_or(Duration::ZERO)
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn only_safe_methods_are_retried_by_default() {
+        let policy = RetryPolicy::default();
+        assert!(policy.should_retry_method(&Method::GET));
+        assert!(policy.should_retry_method(&Method::HEAD));
+        assert!(!policy.should_retry_method(&Method::PUT));
+        assert!(!policy.should_retry_method(&Method::DELETE));
+        assert!(!policy.should_retry_method(&Method::POST));
+
+        let policy = policy.retry_non_idempotent(true);
+        assert!(policy.should_retry_method(&Method::POST));
+    }
+
+    #[test]
+    fn server_delay_is_bounded() {
+        let policy = RetryPolicy::default().max_server_delay(Duration::from_secs(10));
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", HeaderValue::from_static("3"));
+        assert_eq!(
+            policy.delay(1, Some((StatusCode::SERVICE_UNAVAILABLE, &headers))),
+            Duration::from_secs(3)
+        );
+
+        headers.insert("retry-after", HeaderValue::from_static("7200"));
+        assert_eq!(
+            policy.delay(1, Some((StatusCode::SERVICE_UNAVAILABLE, &headers))),
+            Duration::from_secs(10)
+        );
+    }
+
+    #[test]
+    fn backoff_is_exponential_and_bounded() {
+        let policy = RetryPolicy::default()
+            .jitter(false)
+            .backoff(Duration::from_millis(100), Duration::from_millis(350));
+        assert_eq!(policy.delay(1, None), Duration::from_millis(100));
+        assert_eq!(policy.delay(2, None), Duration::from_millis(200));
+        assert_eq!(policy.delay(3, None), Duration::from_millis(350));
+    }
+
+    #[test]
+    fn rate_limit_reset_only_delays_rate_limited_responses() {
+        let policy = RetryPolicy::default()
+            .jitter(false)
+            .backoff(Duration::from_millis(100), Duration::from_secs(1));
+        let reset = (Utc::now().timestamp() + 3600).to_string();
+        let mut headers = HeaderMap::new();
+        headers.insert("ratelimit-reset", HeaderValue::from_str(&reset).unwrap());
+        headers.insert("ratelimit-remaining", HeaderValue::from_static("1999"));
+
+        let delay = |status| policy.delay(1, Some((status, &headers)));
+        assert_eq!(
+            delay(StatusCode::SERVICE_UNAVAILABLE),
+            Duration::from_millis(100)
+        );
+        assert_eq!(
+            delay(StatusCode::TOO_MANY_REQUESTS),
+            policy.max_server_delay
+        );
+
+        headers.insert("ratelimit-remaining", HeaderValue::from_static("0"));
+        let delay = |status| policy.delay(1, Some((status, &headers)));
+        assert_eq!(
+            delay(StatusCode::SERVICE_UNAVAILABLE),
+            policy.max_server_delay
+        );
+    }
+}
@@ -0,0 +1,80 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+use crate::{
+    branch::{AccessLevel, AccessLevelOption},
+    commit::Commit,
+};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TagRelease {
+    pub tag_name: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Tag {
+    pub name: String,
+    /// The message of an annotated tag
+    pub message: Option<String>,
+    pub target: String,
+    pub commit: Commit,
+    pub release: Option<TagRelease>,
+    pub protected: bool,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ListTagsOption {
+    /// `name`, `updated` or `version`
+    pub order_by: Option<String>,
+    pub sort: Option<String>,
+    pub search: Option<String>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct CreateTagOption {
+    pub tag_name: String,
+    /// Commit sha, another tag or branch name
+    #[serde(rename = "ref")]
+    pub ref_: String,
+    /// Create an annotated tag with the message
+    pub message: Option<String>,
+}
+
+impl CreateTagOption {
+    pub fn new(tag_name: &str, ref_: &str) -> Self {
+        Self {
+            tag_name: tag_name.to_string(),
+            ref_: ref_.to_string(),
+            message: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ProtectedTag {
+    pub name: String,
+    pub create_access_levels: Vec<AccessLevel>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ProtectTagOption {
+    /// Tag name or wildcard, e.g. `v*`
+    pub name: String,
+    pub create_access_level: Option<i64>,
+    pub allowed_to_create: Option<Vec<AccessLevelOption>>,
+}
+
+impl ProtectTagOption {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+}
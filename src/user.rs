@@ -47,3 +47,14 @@ pub struct User {
     pub note: String,
     pub using_license_seat: bool,
 }
+
+/// The basic user info embedded in other resources, e.g. the author of a merge request
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BasicUser {
+    pub id: i64,
+    pub name: String,
+    pub username: String,
+    pub state: String,
+    pub avatar_url: Option<String>,
+    pub web_url: Option<String>,
+}
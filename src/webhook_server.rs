@@ -0,0 +1,448 @@
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    future::Future,
+    net::{SocketAddr, TcpListener},
+    sync::Arc,
+};
+
+use futures::future::BoxFuture;
+use http::{header::CONTENT_TYPE, Method, Request, Response, StatusCode};
+use hyper::{
+    body::HttpBody,
+    service::{make_service_fn, service_fn},
+    Body, Server,
+};
+use serde_json::json;
+use tokio::sync::Semaphore;
+
+use crate::{
+    hook::{WebhookEvent, WebhookEventKind, WebhookRejection, WebhookVerifier},
+    Error, Result,
+};
+
+type Handler =
+    Arc<dyn Fn(WebhookEvent) -> BoxFuture<'static, Result<()>> + Send + Sync>;
+type ErrorHandler = Arc<dyn Fn(Error) + Send + Sync>;
+
+/// The permits of a semaphore are bounded by `Semaphore::MAX_PERMITS` (`usize::MAX >>
+/// 3`, not exported by older tokio), and waiting for all of them takes a `u32`
+const MAX_CONCURRENCY: usize = if usize::MAX >> 3 < u32::MAX as usize {
+    usize::MAX >> 3
+} else {
+    u32::MAX as usize
+};
+
+/// An embedded http server receiving webhook events and routing them to handlers.
+///
+/// Requests are verified by a [`WebhookVerifier`] and answered as soon as the event
+/// is handed over to its handler, so that gitlab doesn't time out on slow handlers.
+/// Responses are json, `2xx` for accepted, duplicate and ignored events, `4xx` for
+/// rejected requests and `503` when too many handlers are running.
+pub struct WebhookServer {
+    verifier: WebhookVerifier,
+    handlers: HashMap<WebhookEventKind, Handler>,
+    fallback: Option<Handler>,
+    on_error: Option<ErrorHandler>,
+    path: String,
+    max_concurrency: usize,
+    max_body_size: usize,
+}
+
+impl WebhookServer {
+    pub fn new(verifier: WebhookVerifier) -> Self {
+        Self {
+            verifier,
+            handlers: HashMap::new(),
+            fallback: None,
+            on_error: None,
+            path: "/".to_string(),
+            max_concurrency: 64,
+            max_body_size: 25 * 1024 * 1024,
+        }
+    }
+
+    /// Only accept requests on this path, defaults to `/`
+    pub fn path(mut self, path: &str) -> Self {
+        self.path = path.to_string();
+        self
+    }
+
+    /// Maximum number of handlers running at the same time, further requests are
+    /// answered with `503` and not recorded as delivered, so that they can be
+    /// redelivered. Defaults to 64.
+    pub fn max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency.clamp(1, MAX_CONCURRENCY);
+        self
+    }
+
+    /// Reject bodies larger than `max_body_size` bytes, defaults to 25MB
+    pub fn max_body_size(mut self, max_body_size: usize) -> Self {
+        self.max_body_size = max_body_size;
+        self
+    }
+
+    /// Handle the events of `kind`
+    pub fn on<F, Fut>(mut self, kind: WebhookEventKind, handler: F) -> Self
+    where
+        F: Fn(WebhookEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.handlers
+            .insert(kind, Arc::new(move |event| Box::pin(handler(event))));
+        self
+    }
+
+    /// Handle the events without a handler of their own, including unknown events
+    pub fn fallback<F, Fut>(mut self, handler: F) -> Self
+    where
+        F: Fn(WebhookEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.fallback = Some(Arc::new(move |event| Box::pin(handler(event))));
+        self
+    }
+
+    /// Called with the errors returned by handlers, which can't be reported to
+    /// gitlab as the request is already answered
+    pub fn on_error<F>(mut self, on_error: F) -> Self
+    where
+        F: Fn(Error) + Send + Sync + 'static,
+    {
+        self.on_error = Some(Arc::new(on_error));
+        self
+    }
+
+    /// Serve on `addr` until `shutdown` completes, then wait for the running
+    /// handlers to finish
+    pub async fn serve(
+        self,
+        addr: SocketAddr,
+        shutdown: impl Future<Output = ()>,
+    ) -> Result<()> {
+        let listener = TcpListener::bind(addr)
+            .map_err(|e| Error::Other(format!("bind {} failed: {}", addr, e)))?;
+        self.serve_on(listener, shutdown).await
+    }
+
+    /// Like [`WebhookServer::serve`] on a bound listener, e.g. to learn the port
+    /// picked for `127.0.0.1:0` from its `local_addr`
+    pub async fn serve_on(
+        self,
+        listener: TcpListener,
+        shutdown: impl Future<Output = ()>,
+    ) -> Result<()> {
+        let max_concurrency = self.max_concurrency;
+        let permits = Arc::new(Semaphore::new(max_concurrency));
+        let server = Arc::new(self);
+
+        let make_svc = {
+            let permits = permits.clone();
+            make_service_fn(move |_conn| {
+                let (server, permits) = (server.clone(), permits.clone());
+                async move {
+                    Ok::<_, Infallible>(service_fn(move |req| {
+                        let (server, permits) = (server.clone(), permits.clone());
+                        async move { Ok::<_, Infallible>(server.handle(req, permits).await) }
+                    }))
+                }
+            })
+        };
+
+        Server::from_tcp(listener)
+            .map_err(|e| Error::Other(format!("listen failed: {}", e)))?
+            .serve(make_svc)
+            .with_graceful_shutdown(shutdown)
+            .await
+            .map_err(|e| Error::Other(format!("webhook server failed: {}", e)))?;
+
+        // every permit is released once the running handlers are finished
+        let _ = permits.acquire_many(max_concurrency as u32).await;
+        Ok(())
+    }
+
+    async fn handle(
+        &self,
+        req: Request<Body>,
+        permits: Arc<Semaphore>,
+    ) -> Response<Body> {
+        if req.uri().path() != self.path {
+            return reply(StatusCode::NOT_FOUND, "rejected", Some("not found"));
+        }
+        if req.method() != Method::POST {
+            return reply(
+                StatusCode::METHOD_NOT_ALLOWED,
+                "rejected",
+                Some("method not allowed"),
+            );
+        }
+
+        let (parts, mut body) = req.into_parts();
+        let mut buf = Vec::new();
+        while let Some(chunk) = body.data().await {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    return reply(
+                        StatusCode::BAD_REQUEST,
+                        "rejected",
+                        Some(&e.to_string()),
+                    )
+                }
+            };
+            if buf.len() + chunk.len() > self.max_body_size {
+                return reply(
+                    StatusCode::PAYLOAD_TOO_LARGE,
+                    "rejected",
+                    Some("payload too large"),
+                );
+            }
+            buf.extend_from_slice(&chunk);
+        }
+
+        let event = match self.verifier.verify_payload(&parts.headers, &buf) {
+            Ok(event) => event,
+            Err(rejection) => return reject(&rejection),
+        };
+
+        let handler = event
+            .kind()
+            .and_then(|kind| self.handlers.get(&kind))
+            .or(self.fallback.as_ref());
+        let handler = match handler {
+            Some(handler) => handler.clone(),
+            None => return reply(StatusCode::OK, "ignored", None),
+        };
+
+        let permit = match permits.try_acquire_owned() {
+            Ok(permit) => permit,
+            Err(_) => {
+                return reply(
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "rejected",
+                    Some("too many running handlers"),
+                )
+            }
+        };
+        // the delivery is recorded only once it's sure to be handled
+        if let Err(rejection) = self.verifier.record_delivery(&parts.headers) {
+            return reject(&rejection);
+        }
+        let on_error = self.on_error.clone();
+        tokio::spawn(async move {
+            let _permit = permit;
+            if let (Err(err), Some(on_error)) = (handler(event).await, on_error) {
+                on_error(err);
+            }
+        });
+        reply(StatusCode::ACCEPTED, "accepted", None)
+    }
+}
+
+fn reject(rejection: &WebhookRejection) -> Response<Body> {
+    let status = match rejection {
+        WebhookRejection::MissingToken | WebhookRejection::InvalidToken => {
+            StatusCode::UNAUTHORIZED
+        }
+        WebhookRejection::Duplicate(_) => {
+            return reply(StatusCode::OK, "duplicate", None);
+        }
+        _ => StatusCode::BAD_REQUEST,
+    };
+    reply(status, "rejected", Some(&rejection.to_string()))
+}
+
+fn reply(status: StatusCode, result: &str, error: Option<&str>) -> Response<Body> {
+    let body = match error {
+        Some(error) => json!({ "status": result, "error": error }),
+        None => json!({ "status": result }),
+    };
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .expect("valid response")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicBool, Ordering},
+        time::Duration,
+    };
+
+    use tokio::{
+        sync::{mpsc, oneshot, Notify},
+        task::JoinHandle,
+    };
+
+    use super::*;
+    use crate::hook::{EVENT_HEADER, EVENT_UUID_HEADER, TOKEN_HEADER};
+
+    const PUSH: &str = include_str!("../tests/fixtures/webhook/push.json");
+
+    struct Running {
+        url: String,
+        shutdown: oneshot::Sender<()>,
+        served: JoinHandle<Result<()>>,
+    }
+
+    fn start(server: WebhookServer) -> Running {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let (shutdown, rx) = oneshot::channel::<()>();
+        let served = tokio::spawn(server.path("/hook").serve_on(listener, async {
+            let _ = rx.await;
+        }));
+        Running {
+            url,
+            shutdown,
+            served,
+        }
+    }
+
+    async fn deliver(url: &str, uuid: &str) -> (StatusCode, String) {
+        let resp = reqwest::Client::new()
+            .post(url)
+            .header(EVENT_HEADER, "Push Hook")
+            .header(TOKEN_HEADER, "secret")
+            .header(EVENT_UUID_HEADER, uuid)
+            .body(PUSH)
+            .send()
+            .await
+            .unwrap();
+        let status = resp.status();
+        let body: serde_json::Value = resp.json().await.unwrap();
+        (
+            status,
+            body["status"].as_str().unwrap_or_default().to_string(),
+        )
+    }
+
+    fn verifier() -> WebhookVerifier {
+        WebhookVerifier::new(Some("secret")).dedup(16)
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_requests() {
+        let running = start(
+            WebhookServer::new(verifier())
+                .max_body_size(PUSH.len() - 1)
+                .on(WebhookEventKind::Push, |_| async { Ok(()) }),
+        );
+        let cli = reqwest::Client::new();
+        let root = running.url.trim_end_matches("/hook");
+
+        let resp = cli.post(format!("{}/other", root)).send().await.unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        let resp = cli.get(&running.url).send().await.unwrap();
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(
+            deliver(&running.url, "1").await,
+            (StatusCode::PAYLOAD_TOO_LARGE, "rejected".to_string())
+        );
+        let resp = cli
+            .post(&running.url)
+            .header(EVENT_HEADER, "Push Hook")
+            .header(TOKEN_HEADER, "wrong")
+            .body("{}")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn accepts_ignores_and_dedups() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let running = start(WebhookServer::new(verifier()).on(
+            WebhookEventKind::Push,
+            move |event| {
+                let tx = tx.clone();
+                async move {
+                    tx.send(event.kind()).unwrap();
+                    Ok(())
+                }
+            },
+        ));
+
+        assert_eq!(
+            deliver(&running.url, "1").await,
+            (StatusCode::ACCEPTED, "accepted".to_string())
+        );
+        assert_eq!(rx.recv().await, Some(Some(WebhookEventKind::Push)));
+        assert_eq!(
+            deliver(&running.url, "1").await,
+            (StatusCode::OK, "duplicate".to_string())
+        );
+
+        // no handler for tag pushes and no fallback
+        let resp = reqwest::Client::new()
+            .post(&running.url)
+            .header(EVENT_HEADER, "Tag Push Hook")
+            .header(TOKEN_HEADER, "secret")
+            .body(include_str!("../tests/fixtures/webhook/tag_push.json"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: serde_json::Value = resp.json().await.unwrap();
+        assert_eq!(body["status"], "ignored");
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn saturated_deliveries_can_be_redelivered() {
+        let release = Arc::new(Notify::new());
+        let (done_tx, mut done) = mpsc::unbounded_channel();
+        let running = start({
+            let release = release.clone();
+            WebhookServer::new(verifier()).max_concurrency(1).on(
+                WebhookEventKind::Push,
+                move |_| {
+                    let (release, done_tx) = (release.clone(), done_tx.clone());
+                    async move {
+                        release.notified().await;
+                        done_tx.send(()).unwrap();
+                        Ok(())
+                    }
+                },
+            )
+        });
+
+        assert_eq!(deliver(&running.url, "1").await.0, StatusCode::ACCEPTED);
+        assert_eq!(
+            deliver(&running.url, "2").await,
+            (StatusCode::SERVICE_UNAVAILABLE, "rejected".to_string())
+        );
+
+        release.notify_one();
+        done.recv().await.unwrap();
+        // the permit is released right after the handler returns
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(deliver(&running.url, "2").await.0, StatusCode::ACCEPTED);
+        release.notify_one();
+        done.recv().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn shutdown_waits_for_running_handlers() {
+        let finished = Arc::new(AtomicBool::new(false));
+        let running = start({
+            let finished = finished.clone();
+            WebhookServer::new(verifier()).on(WebhookEventKind::Push, move |_| {
+                let finished = finished.clone();
+                async move {
+                    tokio::time::sleep(Duration::from_millis(300)).await;
+                    finished.store(true, Ordering::SeqCst);
+                    Ok(())
+                }
+            })
+        });
+
+        assert_eq!(deliver(&running.url, "1").await.0, StatusCode::ACCEPTED);
+        running.shutdown.send(()).unwrap();
+        running.served.await.unwrap().unwrap();
+        assert!(finished.load(Ordering::SeqCst));
+    }
+}